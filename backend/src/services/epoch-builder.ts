@@ -9,6 +9,11 @@ import * as crypto from 'crypto';
 
 const logger = pino({ name: 'epoch-builder' });
 
+// Must match the distributor's LEAF_VERSION_V1, LEAF_PREFIX and NODE_PREFIX
+export const LEAF_VERSION_V1 = 1;
+const LEAF_PREFIX = 0x00;
+const NODE_PREFIX = 0x01;
+
 export interface EpochBuilderConfig {
   rpcUrl: string;
   tokenMint: string;
@@ -30,6 +35,7 @@ export interface EpochData {
   startSlot: number;
   endSlot: number;
   merkleRoot: string;
+  leafVersion: number;
   totalRewardsSol: bigint;
   totalRewardsToken: bigint;
   allocations: WalletAllocation[];
@@ -123,6 +129,7 @@ export class EpochBuilder {
       startSlot,
       endSlot: currentSlot,
       merkleRoot,
+      leafVersion: LEAF_VERSION_V1,
       totalRewardsSol: rewardsSol,
       totalRewardsToken: rewardsToken,
       allocations,
@@ -207,11 +214,12 @@ export class EpochBuilder {
     tree: MerkleTree;
     leaves: Buffer[];
   } {
-    // Create leaves: keccak256(wallet, epochId, amountSol, amountToken)
+    // Create v1 leaves: keccak256(LEAF_PREFIX, version, wallet, epochId, amountSol, amountToken)
     const leaves = allocations.map(a => {
       return keccak256(
         Buffer.concat([
-          Buffer.from(a.wallet),
+          Buffer.from([LEAF_PREFIX, LEAF_VERSION_V1]),
+          new PublicKey(a.wallet).toBuffer(),
           this.toLeBytes(BigInt(epochId)),
           this.toLeBytes(a.allocation),
           this.toLeBytes(a.allocationToken),
@@ -219,7 +227,9 @@ export class EpochBuilder {
       );
     });
 
-    const tree = new MerkleTree(leaves, keccak256, { sortPairs: true });
+    // Internal nodes: keccak256(NODE_PREFIX, sorted left, sorted right)
+    const hashNode = (pair: Buffer) => keccak256(Buffer.concat([Buffer.from([NODE_PREFIX]), pair]));
+    const tree = new MerkleTree(leaves, hashNode, { sortPairs: true });
     const merkleRoot = tree.getHexRoot();
 
     return { merkleRoot, tree, leaves };
@@ -1,4 +1,4 @@
-import { Connection, Keypair, PublicKey, Transaction, sendAndConfirmTransaction } from '@solana/web3.js';
+import { Connection, Keypair, Transaction, sendAndConfirmTransaction } from '@solana/web3.js';
 import { PrismaClient } from '@prisma/client';
 import * as cron from 'node-cron';
 import pino from 'pino';
@@ -97,14 +97,16 @@ class Keeper {
       this.connection,
       this.keypair,
       config.controllerProgramId,
-      config.distributorProgramId
+      config.distributorProgramId,
+      config.tokenMint
     );
 
     this.epochPublisher = new EpochPublisher(
       this.connection,
       this.keypair,
       this.prisma,
-      config.distributorProgramId
+      config.distributorProgramId,
+      config.tokenMint
     );
   }
 
@@ -391,14 +393,7 @@ class Keeper {
   }
 
   private async getTreasuryBalance(): Promise<number> {
-    // Get treasury PDA balance
-    const [treasuryPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from('treasury_sol')],
-      new PublicKey(this.config.controllerProgramId)
-    );
-
-    const balance = await this.connection.getBalance(treasuryPda);
-    return balance;
+    return this.routerService.getTreasuryBalance();
   }
 
   private async checkPaused(): Promise<boolean> {
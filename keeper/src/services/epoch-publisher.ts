@@ -1,11 +1,25 @@
-import { Connection, Keypair, PublicKey, Transaction, sendAndConfirmTransaction } from '@solana/web3.js';
+import {
+  Connection,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+  sendAndConfirmTransaction,
+} from '@solana/web3.js';
 import { PrismaClient } from '@prisma/client';
 import { MerkleTree } from 'merkletreejs';
 import keccak256 from 'keccak256';
 import pino from 'pino';
+import * as crypto from 'crypto';
 
 const logger = pino({ name: 'epoch-publisher' });
 
+// Must match the distributor's LEAF_VERSION_V1, LEAF_PREFIX and NODE_PREFIX
+const LEAF_VERSION_V1 = 1;
+const LEAF_PREFIX = 0x00;
+const NODE_PREFIX = 0x01;
+
 interface PublishResult {
   signature: string;
   epochId: number;
@@ -18,17 +32,20 @@ export class EpochPublisher {
   private keypair: Keypair;
   private prisma: PrismaClient;
   private distributorProgramId: string;
+  private tokenMint: string;
 
   constructor(
     connection: Connection,
     keypair: Keypair,
     prisma: PrismaClient,
-    distributorProgramId: string
+    distributorProgramId: string,
+    tokenMint: string
   ) {
     this.connection = connection;
     this.keypair = keypair;
     this.prisma = prisma;
     this.distributorProgramId = distributorProgramId;
+    this.tokenMint = tokenMint;
   }
 
   /**
@@ -62,19 +79,7 @@ export class EpochPublisher {
     }
 
     // Verify merkle root
-    const leaves = epoch.allocations.map(a => 
-      keccak256(
-        Buffer.concat([
-          Buffer.from(a.wallet),
-          this.toLeBytes(BigInt(newEpochId)),
-          this.toLeBytes(a.amountSol),
-          this.toLeBytes(a.amountToken),
-        ])
-      )
-    );
-
-    const tree = new MerkleTree(leaves, keccak256, { sortPairs: true });
-    const computedRoot = tree.getHexRoot();
+    const computedRoot = this.buildTree(newEpochId, epoch.allocations).getHexRoot();
 
     if (computedRoot !== epoch.merkleRoot) {
       throw new Error('Merkle root mismatch - data integrity issue');
@@ -87,7 +92,9 @@ export class EpochPublisher {
       rewardsSol,
       rewardsToken,
       BigInt(startSlot),
-      BigInt(currentSlot)
+      BigInt(currentSlot),
+      epoch.csvHash,
+      epoch.allocations.length
     );
 
     // Update epoch with publish signature
@@ -120,29 +127,67 @@ export class EpochPublisher {
     rewardsSol: bigint,
     rewardsToken: bigint,
     startSlot: bigint,
-    endSlot: bigint
+    endSlot: bigint,
+    csvHash: string | null,
+    leafCount: number
   ): Promise<string> {
-    // Derive PDAs
+    const programId = new PublicKey(this.distributorProgramId);
+
+    // Derive PDAs of this mint's distributor instance
     const [statePda] = PublicKey.findProgramAddressSync(
-      [Buffer.from('distributor_state')],
-      new PublicKey(this.distributorProgramId)
+      [Buffer.from('distributor_state'), new PublicKey(this.tokenMint).toBuffer()],
+      programId
     );
 
     const [epochPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from('epoch'), this.toLeBytes(BigInt(epochId))],
-      new PublicKey(this.distributorProgramId)
+      [Buffer.from('epoch'), statePda.toBuffer(), this.toLeBytes(BigInt(epochId))],
+      programId
     );
 
-    // Build instruction data
-    // Discriminator (8 bytes) + epoch_id (8) + merkle_root (32) + rewards_sol (8) + rewards_token (8) + start_slot (8) + end_slot (8)
-    const discriminator = Buffer.from([/* publish_epoch discriminator */]);
-    
-    // In production, use Anchor's instruction builder
-    // This is a simplified example
-    
-    const tx = new Transaction();
-    // Add the publish_epoch instruction
-    // tx.add(publishEpochInstruction);
+    // Instruction data (Borsh):
+    // discriminator (8) + epoch_id (8) + merkle_root (32) + rewards_sol (8) + rewards_token (8)
+    // + start_slot (8) + end_slot (8) + leaf_version (1) + metadata + vesting (24) + declare_gap (1)
+    const discriminator = crypto.createHash('sha256').update('global:publish_epoch').digest().subarray(0, 8);
+    const contentHash = Buffer.alloc(32);
+    if (csvHash) {
+      Buffer.from(csvHash.replace(/^0x/, ''), 'hex').copy(contentHash);
+    }
+    const uri = Buffer.alloc(0); // Dataset URI is optional; the CSV hash commits to the contents
+    const metadata = Buffer.concat([
+      contentHash,
+      this.toU32LeBytes(uri.length),
+      uri,
+      this.toU32LeBytes(leafCount),
+      Buffer.from([0, 0]), // methodology_version
+    ]);
+    const vesting = Buffer.alloc(24); // No vesting: start_time, cliff_seconds, duration_seconds = 0
+
+    const data = Buffer.concat([
+      discriminator,
+      this.toLeBytes(BigInt(epochId)),
+      Buffer.from(merkleRoot.replace(/^0x/, ''), 'hex'),
+      this.toLeBytes(rewardsSol),
+      this.toLeBytes(rewardsToken),
+      this.toLeBytes(startSlot),
+      this.toLeBytes(endSlot),
+      Buffer.from([LEAF_VERSION_V1]),
+      metadata,
+      vesting,
+      Buffer.from([0]), // declare_gap
+    ]);
+
+    const tx = new Transaction().add(
+      new TransactionInstruction({
+        programId,
+        keys: [
+          { pubkey: statePda, isSigner: false, isWritable: true },
+          { pubkey: epochPda, isSigner: false, isWritable: true },
+          { pubkey: this.keypair.publicKey, isSigner: true, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data,
+      })
+    );
 
     const signature = await sendAndConfirmTransaction(
       this.connection,
@@ -165,10 +210,23 @@ export class EpochPublisher {
 
     if (!epoch) return false;
 
-    const leaves = epoch.allocations.map(a =>
+    const computedRoot = this.buildTree(epochId, epoch.allocations).getHexRoot();
+
+    return computedRoot === epoch.merkleRoot;
+  }
+
+  /**
+   * Rebuild the v1 (domain-separated) merkle tree of an epoch's allocations
+   */
+  private buildTree(
+    epochId: number,
+    allocations: Array<{ wallet: string; amountSol: bigint; amountToken: bigint }>
+  ): MerkleTree {
+    const leaves = allocations.map(a =>
       keccak256(
         Buffer.concat([
-          Buffer.from(a.wallet),
+          Buffer.from([LEAF_PREFIX, LEAF_VERSION_V1]),
+          new PublicKey(a.wallet).toBuffer(),
           this.toLeBytes(BigInt(epochId)),
           this.toLeBytes(a.amountSol),
           this.toLeBytes(a.amountToken),
@@ -176,10 +234,8 @@ export class EpochPublisher {
       )
     );
 
-    const tree = new MerkleTree(leaves, keccak256, { sortPairs: true });
-    const computedRoot = tree.getHexRoot();
-
-    return computedRoot === epoch.merkleRoot;
+    const hashNode = (pair: Buffer) => keccak256(Buffer.concat([Buffer.from([NODE_PREFIX]), pair]));
+    return new MerkleTree(leaves, hashNode, { sortPairs: true });
   }
 
   /**
@@ -203,6 +259,12 @@ export class EpochPublisher {
     buf.writeBigUInt64LE(value);
     return buf;
   }
+
+  private toU32LeBytes(value: number): Buffer {
+    const buf = Buffer.alloc(4);
+    buf.writeUInt32LE(value);
+    return buf;
+  }
 }
 
 
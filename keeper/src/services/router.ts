@@ -15,17 +15,20 @@ export class RouterService {
   private keypair: Keypair;
   private controllerProgramId: string;
   private distributorProgramId: string;
+  private tokenMint: PublicKey;
 
   constructor(
     connection: Connection,
     keypair: Keypair,
     controllerProgramId: string,
-    distributorProgramId: string
+    distributorProgramId: string,
+    tokenMint: string
   ) {
     this.connection = connection;
     this.keypair = keypair;
     this.controllerProgramId = controllerProgramId;
     this.distributorProgramId = distributorProgramId;
+    this.tokenMint = new PublicKey(tokenMint);
   }
 
   /**
@@ -33,11 +36,7 @@ export class RouterService {
    */
   async getWeights(): Promise<Weights> {
     try {
-      // Derive controller state PDA
-      const [statePda] = PublicKey.findProgramAddressSync(
-        [Buffer.from('controller_state')],
-        new PublicKey(this.controllerProgramId)
-      );
+      const statePda = this.controllerState();
 
       // Fetch and decode state
       const stateAccount = await this.connection.getAccountInfo(statePda);
@@ -53,9 +52,9 @@ export class RouterService {
       }
 
       // Decode weights from account data
-      // Skip 8-byte discriminator + 32-byte admin + 1-byte paused
+      // Skip 8-byte discriminator + 32-byte admin + 32-byte mint + 1-byte paused
       const data = stateAccount.data;
-      const weightsOffset = 8 + 32 + 1;
+      const weightsOffset = 8 + 32 + 32 + 1;
       
       return {
         rewards: data[weightsOffset],
@@ -80,17 +79,18 @@ export class RouterService {
    */
   async routeToRewards(amount: number): Promise<string> {
     try {
-      // Derive distributor SOL vault PDA
+      // Derive the SOL vault PDA of this mint's distributor instance
+      const distributorProgram = new PublicKey(this.distributorProgramId);
+      const [distributorState] = PublicKey.findProgramAddressSync(
+        [Buffer.from('distributor_state'), this.tokenMint.toBuffer()],
+        distributorProgram
+      );
       const [distributorVault] = PublicKey.findProgramAddressSync(
-        [Buffer.from('sol_vault')],
-        new PublicKey(this.distributorProgramId)
+        [Buffer.from('sol_vault'), distributorState.toBuffer()],
+        distributorProgram
       );
 
-      // Derive treasury vault
-      const [treasuryVault] = PublicKey.findProgramAddressSync(
-        [Buffer.from('treasury_sol')],
-        new PublicKey(this.controllerProgramId)
-      );
+      const treasuryVault = this.treasuryVault();
 
       // Build route_funds instruction
       // This would call the controller program's route_funds instruction
@@ -187,12 +187,29 @@ export class RouterService {
    * Get treasury balance
    */
   async getTreasuryBalance(): Promise<number> {
-    const [treasuryVault] = PublicKey.findProgramAddressSync(
-      [Buffer.from('treasury_sol')],
+    return await this.connection.getBalance(this.treasuryVault());
+  }
+
+  /**
+   * Controller state PDA of this mint's instance
+   */
+  private controllerState(): PublicKey {
+    const [statePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('controller_state'), this.tokenMint.toBuffer()],
       new PublicKey(this.controllerProgramId)
     );
+    return statePda;
+  }
 
-    return await this.connection.getBalance(treasuryVault);
+  /**
+   * Treasury SOL vault PDA of this mint's controller instance
+   */
+  private treasuryVault(): PublicKey {
+    const [treasuryVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from('treasury_sol'), this.controllerState().toBuffer()],
+      new PublicKey(this.controllerProgramId)
+    );
+    return treasuryVault;
   }
 }
 
@@ -14,8 +14,8 @@ pub enum ControllerError {
     SlippageExceeded,
     #[msg("Trade size exceeds maximum")]
     TradeSizeExceeded,
-    #[msg("Daily trade limit exceeded")]
-    DailyLimitExceeded,
+    #[msg("Trade limit for the current period exceeded")]
+    TradeLimitExceeded,
     #[msg("DEX program not allowed")]
     DexNotAllowed,
     #[msg("Maximum keepers reached")]
@@ -32,6 +32,74 @@ pub enum ControllerError {
     ZeroAmount,
     #[msg("Invalid amount")]
     InvalidAmount,
+    #[msg("Keeper is suspended")]
+    KeeperSuspended,
+    #[msg("Keeper lacks permission for this action")]
+    MissingPermission,
+    #[msg("Amount exceeds keeper limit")]
+    KeeperLimitExceeded,
+    #[msg("Unknown permission bits")]
+    InvalidPermissions,
+    #[msg("DEX program already in allowlist")]
+    DexAlreadyAllowed,
+    #[msg("DEX program not in allowlist")]
+    DexNotFound,
+    #[msg("Swap volume limit exceeded")]
+    VolumeLimitExceeded,
+    #[msg("Invalid volume limits")]
+    InvalidVolumeLimits,
+    #[msg("Minimum interval since the last swap has not elapsed")]
+    SwapCooldown,
+    #[msg("Invalid execution plan")]
+    InvalidExecutionPlan,
+    #[msg("Execution plan expired")]
+    ExecutionPlanExpired,
+    #[msg("Swap exceeds the execution plan chunk or remaining amount")]
+    ExecutionPlanExceeded,
+    #[msg("Price oracle not configured")]
+    OracleNotConfigured,
+    #[msg("Invalid oracle configuration")]
+    InvalidOracleConfig,
+    #[msg("Oracle account does not match configuration")]
+    OracleMismatch,
+    #[msg("Invalid oracle account")]
+    InvalidOracleAccount,
+    #[msg("Invalid oracle price")]
+    InvalidOraclePrice,
+    #[msg("Oracle price is stale")]
+    StaleOraclePrice,
+    #[msg("Oracle confidence interval too wide")]
+    OracleConfidenceTooWide,
+    #[msg("Invalid pool configuration")]
+    InvalidPoolConfig,
+    #[msg("Invalid pool account")]
+    InvalidPoolAccount,
+    #[msg("Pool price deviates too far from reference")]
+    PoolPriceDeviation,
+    #[msg("Swap requires an oracle or pool price check")]
+    PriceCheckRequired,
+    #[msg("Invalid circuit breaker configuration")]
+    InvalidCircuitBreaker,
+    #[msg("Swap guard enabled: use begin_swap and end_swap")]
+    SwapGuardRequired,
+    #[msg("Transaction invokes a program not approved for guarded swaps")]
+    UnapprovedProgram,
+    #[msg("Guarded swap must be begin_swap, DEX swap, end_swap in sequence")]
+    InvalidSwapComposition,
+    #[msg("Invalid treasury token account")]
+    InvalidTokenAccount,
+    #[msg("Max slippage exceeds 10000 bps")]
+    InvalidSlippage,
+    #[msg("Maximum approved programs reached")]
+    MaxApprovedProgramsReached,
+    #[msg("Program already approved")]
+    ProgramAlreadyApproved,
+    #[msg("Program not approved")]
+    ProgramNotApproved,
+    #[msg("Swap must fill the active execution plan")]
+    ExecutionPlanRequired,
+    #[msg("An execution plan is already active")]
+    ExecutionPlanActive,
 }
 
 
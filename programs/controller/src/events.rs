@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+/// Why the circuit breaker paused the controller
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TripReason {
+    /// Treasury outflow in the window exceeded the allowed share of the vault
+    Outflow,
+    /// A reported swap realized more slippage than allowed
+    Slippage,
+    /// Too many keeper failures reported in the window
+    Failures,
+}
+
+#[event]
+pub struct CircuitBreakerTripped {
+    pub controller: Pubkey,
+    pub reason: TripReason,
+    /// Observed value that crossed the limit
+    pub observed: u64,
+    /// Configured limit
+    pub limit: u64,
+    pub timestamp: i64,
+}
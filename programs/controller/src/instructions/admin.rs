@@ -6,7 +6,7 @@ use crate::error::ControllerError;
 pub struct UpdateAdmin<'info> {
     #[account(
         mut,
-        seeds = [b"controller_state"],
+        seeds = [b"controller_state", state.mint.as_ref()],
         bump = state.bump,
         constraint = admin.key() == state.admin @ ControllerError::UnauthorizedAdmin
     )]
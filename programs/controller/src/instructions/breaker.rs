@@ -0,0 +1,152 @@
+use anchor_lang::prelude::*;
+use crate::state::{ControllerState, KeeperAccount, PERMISSION_REPORT};
+use crate::error::ControllerError;
+use crate::events::{CircuitBreakerTripped, TripReason};
+
+#[derive(Accounts)]
+pub struct ReportToBreaker<'info> {
+    #[account(
+        mut,
+        seeds = [b"controller_state", state.mint.as_ref()],
+        bump = state.bump
+    )]
+    pub state: Account<'info, ControllerState>,
+
+    /// CHECK: Treasury SOL vault
+    #[account(
+        seeds = [b"treasury_sol", state.key().as_ref()],
+        bump = state.sol_vault_bump
+    )]
+    pub sol_vault: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"keeper", state.key().as_ref(), keeper.key().as_ref()],
+        bump = keeper_account.bump,
+        constraint = keeper_account.is_active() @ ControllerError::KeeperSuspended
+    )]
+    pub keeper_account: Account<'info, KeeperAccount>,
+
+    pub keeper: Signer<'info>,
+}
+
+/// Keeper reports the realized output of a swap; trips the breaker on a slippage spike
+pub fn report_swap_result_handler(
+    ctx: Context<ReportToBreaker>,
+    expected_out: u64,
+    actual_out: u64,
+) -> Result<()> {
+    require!(
+        ctx.accounts.keeper_account.has_permission(PERMISSION_REPORT),
+        ControllerError::MissingPermission
+    );
+    require!(expected_out > 0, ControllerError::ZeroAmount);
+
+    let now = Clock::get()?.unix_timestamp;
+    let slippage_bps = record_realized_slippage(&mut ctx.accounts.state, expected_out, actual_out, now);
+
+    msg!(
+        "Swap result reported: expected {}, actual {} ({} bps slippage)",
+        expected_out,
+        actual_out,
+        slippage_bps
+    );
+
+    Ok(())
+}
+
+/// Keeper reports a failed operation; trips the breaker once the window's failures reach the limit
+pub fn report_failure_handler(ctx: Context<ReportToBreaker>, code: u32) -> Result<()> {
+    require!(
+        ctx.accounts.keeper_account.has_permission(PERMISSION_REPORT),
+        ControllerError::MissingPermission
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let vault_balance = ctx.accounts.sol_vault.lamports();
+    let state = &mut ctx.accounts.state;
+    roll_window(state, vault_balance, now);
+    state.breaker_window.failures = state.breaker_window.failures.saturating_add(1);
+
+    msg!(
+        "Failure {} reported by {} ({} in window)",
+        code,
+        ctx.accounts.keeper.key(),
+        state.breaker_window.failures
+    );
+
+    let limit = state.breaker.max_failures as u64;
+    let failures = state.breaker_window.failures as u64;
+    if limit > 0 && failures >= limit {
+        trip(state, TripReason::Failures, failures, limit, now);
+    }
+
+    Ok(())
+}
+
+/// Trip the breaker if `actual_out` fell short of `expected_out` by more than the
+/// realized slippage limit. Returns the realized slippage in basis points.
+pub fn record_realized_slippage(
+    state: &mut Account<ControllerState>,
+    expected_out: u64,
+    actual_out: u64,
+    now: i64,
+) -> u64 {
+    if expected_out == 0 {
+        return 0;
+    }
+    let slippage_bps = ((expected_out.saturating_sub(actual_out) as u128) * 10_000 / (expected_out as u128)) as u64;
+    let limit = state.breaker.max_realized_slippage_bps as u64;
+    if limit > 0 && slippage_bps > limit {
+        trip(state, TripReason::Slippage, slippage_bps, limit, now);
+    }
+    slippage_bps
+}
+
+/// Count `amount` leaving the treasury against the window's outflow limit.
+/// Returns false, after pausing the controller, if the outflow would exceed it.
+pub fn record_outflow(state: &mut Account<ControllerState>, vault_balance: u64, amount: u64, now: i64) -> bool {
+    roll_window(state, vault_balance, now);
+    let outflow = state.breaker_window.outflow.saturating_add(amount);
+
+    let max_bps = state.breaker.max_outflow_bps as u128;
+    if max_bps > 0 {
+        let limit = (state.breaker_window.start_balance as u128) * max_bps / 10_000;
+        if outflow as u128 > limit {
+            trip(state, TripReason::Outflow, outflow, limit as u64, now);
+            return false;
+        }
+    }
+
+    state.breaker_window.outflow = outflow;
+    true
+}
+
+/// Start a new breaker window once the configured length has elapsed
+fn roll_window(state: &mut ControllerState, vault_balance: u64, now: i64) {
+    let window = &mut state.breaker_window;
+    if now >= window.start.saturating_add(state.breaker.window_seconds) {
+        window.start = now;
+        window.start_balance = vault_balance;
+        window.outflow = 0;
+        window.failures = 0;
+    }
+}
+
+fn trip(state: &mut Account<ControllerState>, reason: TripReason, observed: u64, limit: u64, now: i64) {
+    state.paused = true;
+    state.trip_reason = Some(reason);
+
+    msg!(
+        "Circuit breaker tripped: {:?} ({} > {}), controller paused",
+        reason,
+        observed,
+        limit
+    );
+    emit!(CircuitBreakerTripped {
+        controller: state.key(),
+        reason,
+        observed,
+        limit,
+        timestamp: now,
+    });
+}
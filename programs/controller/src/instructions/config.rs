@@ -1,13 +1,13 @@
 use anchor_lang::prelude::*;
-use crate::state::{ControllerState, MAX_KEEPERS, MAX_DEX_PROGRAMS};
+use crate::state::{ControllerState, MAX_APPROVED_PROGRAMS, MAX_DEX_PROGRAMS, MAX_SLIPPAGE_BPS};
 use crate::error::ControllerError;
-use crate::RoutingWeights;
+use crate::{CircuitBreakerConfig, DexEntry, OracleConfig, RoutingWeights, VolumeLimits};
 
 #[derive(Accounts)]
 pub struct UpdateConfig<'info> {
     #[account(
         mut,
-        seeds = [b"controller_state"],
+        seeds = [b"controller_state", state.mint.as_ref()],
         bump = state.bump,
         constraint = admin.key() == state.admin @ ControllerError::UnauthorizedAdmin
     )]
@@ -34,6 +34,8 @@ pub fn update_weights_handler(ctx: Context<UpdateConfig>, weights: RoutingWeight
 }
 
 pub fn update_slippage_handler(ctx: Context<UpdateConfig>, max_slippage_bps: u16) -> Result<()> {
+    require!(max_slippage_bps <= MAX_SLIPPAGE_BPS, ControllerError::InvalidSlippage);
+
     let state = &mut ctx.accounts.state;
     state.max_slippage_bps = max_slippage_bps;
     
@@ -51,66 +53,203 @@ pub fn update_max_trade_handler(ctx: Context<UpdateConfig>, max_trade_lamports:
     Ok(())
 }
 
-pub fn add_keeper_handler(ctx: Context<UpdateConfig>, keeper: Pubkey) -> Result<()> {
+pub fn update_volume_limits_handler(ctx: Context<UpdateConfig>, limits: VolumeLimits) -> Result<()> {
+    require!(limits.validate(), ControllerError::InvalidVolumeLimits);
+    
     let state = &mut ctx.accounts.state;
+    state.volume_limits = limits;
     
-    require!(
-        (state.num_keepers as usize) < MAX_KEEPERS,
-        ControllerError::MaxKeepersReached
+    msg!(
+        "Volume limits updated: {} lamports per {}s period, {} lamports per {}s window",
+        limits.max_period_lamports,
+        limits.period_seconds,
+        limits.max_window_lamports,
+        limits.window_seconds
     );
     
-    state.keepers[state.num_keepers as usize] = keeper;
-    state.num_keepers += 1;
+    Ok(())
+}
+
+pub fn update_swap_interval_handler(ctx: Context<UpdateConfig>, min_swap_interval_seconds: i64) -> Result<()> {
+    require!(min_swap_interval_seconds >= 0, ControllerError::InvalidAmount);
+    
+    let state = &mut ctx.accounts.state;
+    state.min_swap_interval_seconds = min_swap_interval_seconds;
     
-    msg!("Keeper added: {}", keeper);
+    msg!("Minimum swap interval updated to {} seconds", min_swap_interval_seconds);
     
     Ok(())
 }
 
-pub fn remove_keeper_handler(ctx: Context<UpdateConfig>, keeper: Pubkey) -> Result<()> {
+pub fn update_oracle_handler(ctx: Context<UpdateConfig>, oracle: OracleConfig) -> Result<()> {
+    require!(oracle.validate(), ControllerError::InvalidOracleConfig);
+    
     let state = &mut ctx.accounts.state;
+    state.oracle = oracle;
     
-    let mut found_idx: Option<usize> = None;
-    for i in 0..state.num_keepers as usize {
-        if state.keepers[i] == keeper {
-            found_idx = Some(i);
-            break;
-        }
-    }
+    msg!(
+        "Oracle updated: {} (max staleness {}s, max confidence {} bps)",
+        oracle.price_account,
+        oracle.max_staleness_seconds,
+        oracle.max_confidence_bps
+    );
     
-    let idx = found_idx.ok_or(ControllerError::KeeperNotFound)?;
+    Ok(())
+}
+
+pub fn update_circuit_breaker_handler(ctx: Context<UpdateConfig>, breaker: CircuitBreakerConfig) -> Result<()> {
+    require!(breaker.validate(), ControllerError::InvalidCircuitBreaker);
     
-    // Shift remaining keepers down
-    for i in idx..(state.num_keepers as usize - 1) {
-        state.keepers[i] = state.keepers[i + 1];
-    }
-    state.keepers[state.num_keepers as usize - 1] = Pubkey::default();
-    state.num_keepers -= 1;
+    let state = &mut ctx.accounts.state;
+    state.breaker = breaker;
     
-    msg!("Keeper removed: {}", keeper);
+    msg!(
+        "Circuit breaker updated: window {}s, max outflow {} bps, max slippage {} bps, max failures {}",
+        breaker.window_seconds,
+        breaker.max_outflow_bps,
+        breaker.max_realized_slippage_bps,
+        breaker.max_failures
+    );
     
     Ok(())
 }
 
-pub fn add_dex_handler(ctx: Context<UpdateConfig>, dex_program: Pubkey) -> Result<()> {
+pub fn add_dex_handler(ctx: Context<UpdateConfig>, entry: DexEntry) -> Result<()> {
     let state = &mut ctx.accounts.state;
     
     require!(
         (state.num_dex_programs as usize) < MAX_DEX_PROGRAMS,
         ControllerError::MaxDexProgramsReached
     );
+    require!(
+        state.find_dex(&entry.program_id).is_none(),
+        ControllerError::DexAlreadyAllowed
+    );
+    require!(
+        state.find_approved_program(&entry.program_id).is_none(),
+        ControllerError::ProgramAlreadyApproved
+    );
     
-    state.allowed_dex_programs[state.num_dex_programs as usize] = dex_program;
+    let idx = state.num_dex_programs as usize;
+    state.allowed_dexes[idx] = entry;
     state.num_dex_programs += 1;
     
-    msg!("DEX program added: {}", dex_program);
+    msg!("DEX program added: {}", entry.program_id);
+    
+    Ok(())
+}
+
+pub fn remove_dex_handler(ctx: Context<UpdateConfig>, dex_program: Pubkey) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    
+    let idx = state
+        .find_dex(&dex_program)
+        .ok_or(ControllerError::DexNotFound)?;
+    
+    // Shift remaining entries down
+    let last = state.num_dex_programs as usize - 1;
+    for i in idx..last {
+        state.allowed_dexes[i] = state.allowed_dexes[i + 1];
+    }
+    state.allowed_dexes[last] = DexEntry::default();
+    state.num_dex_programs -= 1;
+    
+    msg!("DEX program removed: {}", dex_program);
+    
+    Ok(())
+}
+
+pub fn replace_dex_handler(ctx: Context<UpdateConfig>, dex_program: Pubkey, entry: DexEntry) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    
+    let idx = state
+        .find_dex(&dex_program)
+        .ok_or(ControllerError::DexNotFound)?;
+    if entry.program_id != dex_program {
+        require!(
+            state.find_dex(&entry.program_id).is_none(),
+            ControllerError::DexAlreadyAllowed
+        );
+        require!(
+            state.find_approved_program(&entry.program_id).is_none(),
+            ControllerError::ProgramAlreadyApproved
+        );
+    }
+    
+    state.allowed_dexes[idx] = entry;
+    
+    msg!(
+        "DEX program {} replaced by {} (enabled: {}, max trade {})",
+        dex_program,
+        entry.program_id,
+        entry.enabled,
+        entry.max_trade_lamports
+    );
     
     Ok(())
 }
 
+pub fn set_swap_guard_handler(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    state.swap_guard_enabled = enabled;
+    
+    msg!("Swap guard set to: {}", enabled);
+    
+    Ok(())
+}
+
+pub fn add_approved_program_handler(ctx: Context<UpdateConfig>, program_id: Pubkey) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+
+    require!(
+        (state.num_approved_programs as usize) < MAX_APPROVED_PROGRAMS,
+        ControllerError::MaxApprovedProgramsReached
+    );
+    require!(
+        state.find_approved_program(&program_id).is_none(),
+        ControllerError::ProgramAlreadyApproved
+    );
+    // A DEX may only run in the guarded slot, where its output is checked
+    require!(
+        state.find_dex(&program_id).is_none(),
+        ControllerError::DexAlreadyAllowed
+    );
+
+    let idx = state.num_approved_programs as usize;
+    state.approved_programs[idx] = program_id;
+    state.num_approved_programs += 1;
+
+    msg!("Approved program added: {}", program_id);
+
+    Ok(())
+}
+
+pub fn remove_approved_program_handler(ctx: Context<UpdateConfig>, program_id: Pubkey) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+
+    let idx = state
+        .find_approved_program(&program_id)
+        .ok_or(ControllerError::ProgramNotApproved)?;
+
+    // Shift remaining entries down
+    let last = state.num_approved_programs as usize - 1;
+    for i in idx..last {
+        state.approved_programs[i] = state.approved_programs[i + 1];
+    }
+    state.approved_programs[last] = Pubkey::default();
+    state.num_approved_programs -= 1;
+
+    msg!("Approved program removed: {}", program_id);
+
+    Ok(())
+}
+
 pub fn set_paused_handler(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
     let state = &mut ctx.accounts.state;
     state.paused = paused;
+    if !paused {
+        state.trip_reason = None;
+    }
     
     msg!("Controller paused state set to: {}", paused);
     
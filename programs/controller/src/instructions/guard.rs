@@ -0,0 +1,662 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as sysvar_instructions, load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_lang::Discriminator;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer};
+use crate::state::{ControllerState, ExecutionPlan, KeeperAccount, PoolConfig, SwapSession, PERMISSION_SWAP};
+use crate::error::ControllerError;
+use crate::instructions::breaker::record_realized_slippage;
+use crate::instructions::swap::{
+    check_price_bounds, check_swap_limits, fill_execution_plan, record_swap, SwapRequest,
+};
+use crate::SwapDirection;
+
+/// Compute budget program, allowed alongside a guarded swap
+const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = pubkey!("ComputeBudget111111111111111111111111111111");
+/// Associated token account program, allowed alongside a guarded swap
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+#[derive(Accounts)]
+pub struct BeginSwap<'info> {
+    #[account(
+        mut,
+        seeds = [b"controller_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = !state.paused @ ControllerError::Paused
+    )]
+    pub state: Account<'info, ControllerState>,
+
+    /// CHECK: Treasury SOL vault
+    #[account(
+        mut,
+        seeds = [b"treasury_sol", state.key().as_ref()],
+        bump = state.sol_vault_bump
+    )]
+    pub sol_vault: AccountInfo<'info>,
+
+    /// Treasury token account the output lands in (SOL-to-token) or the input comes from (token-to-SOL)
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == sol_vault.key() @ ControllerError::InvalidTokenAccount
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    /// Keeper token account receiving the input of a token-to-SOL swap
+    #[account(
+        mut,
+        constraint = keeper_token_account.mint == treasury_token_account.mint @ ControllerError::InvalidTokenAccount,
+        constraint = keeper_token_account.owner == keeper.key() @ ControllerError::InvalidTokenAccount
+    )]
+    pub keeper_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Treasury wSOL account the output of a token-to-SOL swap lands in
+    #[account(
+        constraint = treasury_wsol_account.owner == sol_vault.key() @ ControllerError::InvalidTokenAccount,
+        constraint = treasury_wsol_account.mint == token::spl_token::native_mint::ID @ ControllerError::InvalidTokenAccount
+    )]
+    pub treasury_wsol_account: Option<Account<'info, TokenAccount>>,
+
+    /// Fails to initialize while another guarded swap is in flight
+    #[account(
+        init,
+        payer = keeper,
+        space = SwapSession::LEN,
+        seeds = [b"swap_session", state.key().as_ref()],
+        bump
+    )]
+    pub swap_session: Account<'info, SwapSession>,
+
+    #[account(
+        mut,
+        seeds = [b"keeper", state.key().as_ref(), keeper.key().as_ref()],
+        bump = keeper_account.bump,
+        constraint = keeper_account.is_active() @ ControllerError::KeeperSuspended,
+        constraint = keeper_account.has_permission(PERMISSION_SWAP) @ ControllerError::MissingPermission
+    )]
+    pub keeper_account: Account<'info, KeeperAccount>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    /// CHECK: DEX program the next instruction must invoke
+    pub dex_program: AccountInfo<'info>,
+
+    /// CHECK: Price oracle, must be the configured price account; parsed in the handler
+    #[account(address = state.oracle.price_account @ ControllerError::OracleMismatch)]
+    pub oracle: Option<AccountInfo<'info>>,
+
+    /// Registered pool of the swap's DEX
+    #[account(
+        seeds = [b"pool", state.key().as_ref(), pool_config.pool.as_ref()],
+        bump = pool_config.bump,
+        constraint = pool_config.dex_program == dex_program.key() @ ControllerError::InvalidPoolConfig
+    )]
+    pub pool_config: Option<Account<'info, PoolConfig>>,
+
+    /// CHECK: Pool account named by `pool_config`; checked and parsed in the handler
+    pub pool: Option<AccountInfo<'info>>,
+
+    /// Active plan this swap fills a chunk of; required while one is open
+    #[account(
+        mut,
+        seeds = [b"execution_plan", state.key().as_ref(), &execution_plan.plan_id.to_le_bytes()],
+        bump = execution_plan.bump,
+        constraint = execution_plan.key() == state.active_plan @ ControllerError::ExecutionPlanRequired
+    )]
+    pub execution_plan: Option<Account<'info, ExecutionPlan>>,
+
+    /// CHECK: Instructions sysvar, checked by address
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Open a guarded swap: check limits and transaction composition, then release
+/// `amount_in` (lamports or treasury tokens) to the keeper for the DEX instruction that follows.
+pub fn begin_swap_handler(
+    ctx: Context<BeginSwap>,
+    direction: SwapDirection,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<()> {
+    let dex_program = ctx.accounts.dex_program.key();
+    verify_swap_composition(
+        &ctx.accounts.instructions_sysvar,
+        &ctx.accounts.state.key(),
+        &ctx.accounts.state,
+        &dex_program,
+        &ctx.accounts.swap_session.key(),
+    )?;
+
+    require!(amount_in > 0, ControllerError::ZeroAmount);
+
+    let mut request = SwapRequest {
+        direction,
+        amount_in,
+        min_amount_out,
+        expected_out: 0,
+    };
+    let now = Clock::get()?.unix_timestamp;
+    let token_mint = ctx.accounts.treasury_token_account.mint;
+    let vault_balance = ctx.accounts.sol_vault.lamports();
+    // A DEX pays SOL out as wSOL, so token-to-SOL output is measured on a treasury wSOL account
+    let (source_balance, output_account, balance_before) = match direction {
+        SwapDirection::SolToToken => {
+            require!(
+                token_mint == ctx.accounts.state.mint,
+                ControllerError::InvalidTokenAccount
+            );
+            let treasury_token_account = &ctx.accounts.treasury_token_account;
+            (vault_balance, treasury_token_account.key(), treasury_token_account.amount)
+        }
+        SwapDirection::TokenToSol => {
+            let treasury_wsol_account = ctx
+                .accounts
+                .treasury_wsol_account
+                .as_ref()
+                .ok_or(ControllerError::InvalidTokenAccount)?;
+            (
+                ctx.accounts.treasury_token_account.amount,
+                treasury_wsol_account.key(),
+                treasury_wsol_account.amount,
+            )
+        }
+    };
+
+    request.expected_out = check_price_bounds(
+        &ctx.accounts.state,
+        &token_mint,
+        ctx.accounts.oracle.as_ref(),
+        ctx.accounts.pool_config.as_ref(),
+        ctx.accounts.pool.as_ref(),
+        &request,
+        now,
+    )?;
+
+    // A tripped breaker keeps the pause and releases nothing; the empty session is
+    // closed by the end_swap that follows
+    let session = &mut ctx.accounts.swap_session;
+    session.keeper = ctx.accounts.keeper.key();
+    session.dex_program = dex_program;
+    session.output_account = output_account;
+    session.direction = direction;
+    session.bump = ctx.bumps.swap_session;
+    if !check_swap_limits(
+        &mut ctx.accounts.state,
+        &mut ctx.accounts.keeper_account,
+        &dex_program,
+        vault_balance,
+        source_balance,
+        &request,
+        now,
+    )? {
+        return Ok(());
+    }
+    let lamports = request.lamports();
+
+    // Fill the next chunk of the active execution plan
+    require!(
+        ctx.accounts.state.active_plan == Pubkey::default() || ctx.accounts.execution_plan.is_some(),
+        ControllerError::ExecutionPlanRequired
+    );
+    if let Some(plan) = ctx.accounts.execution_plan.as_mut() {
+        fill_execution_plan(plan, lamports, now)?;
+    }
+
+    let session = &mut ctx.accounts.swap_session;
+    session.amount_in = amount_in;
+    session.lamports = lamports;
+    session.min_amount_out = min_amount_out;
+    session.balance_before = balance_before;
+    session.expected_out = request.expected_out;
+
+    // Release the input to the keeper for the DEX instruction
+    match direction {
+        SwapDirection::SolToToken => {
+            **ctx.accounts.sol_vault.try_borrow_mut_lamports()? -= amount_in;
+            **ctx.accounts.keeper.try_borrow_mut_lamports()? += amount_in;
+        }
+        SwapDirection::TokenToSol => {
+            let keeper_token_account = ctx
+                .accounts
+                .keeper_token_account
+                .as_ref()
+                .ok_or(ControllerError::InvalidTokenAccount)?;
+            let token_program = ctx
+                .accounts
+                .token_program
+                .as_ref()
+                .ok_or(ControllerError::InvalidTokenAccount)?;
+
+            let state_key = ctx.accounts.state.key();
+            let seeds = &[
+                b"treasury_sol".as_ref(),
+                state_key.as_ref(),
+                &[ctx.accounts.state.sol_vault_bump],
+            ];
+            let signer = &[&seeds[..]];
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.treasury_token_account.to_account_info(),
+                        to: keeper_token_account.to_account_info(),
+                        authority: ctx.accounts.sol_vault.to_account_info(),
+                    },
+                    signer,
+                ),
+                amount_in,
+            )?;
+        }
+    }
+
+    msg!(
+        "Guarded swap opened: {:?} {} in -> min {} out via DEX {}",
+        direction,
+        amount_in,
+        min_amount_out,
+        dex_program
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct EndSwap<'info> {
+    #[account(
+        mut,
+        seeds = [b"controller_state", state.mint.as_ref()],
+        bump = state.bump
+    )]
+    pub state: Account<'info, ControllerState>,
+
+    /// CHECK: Treasury SOL vault, owner of the output account and recipient of unwrapped wSOL
+    #[account(
+        mut,
+        seeds = [b"treasury_sol", state.key().as_ref()],
+        bump = state.sol_vault_bump
+    )]
+    pub sol_vault: AccountInfo<'info>,
+
+    /// Treasury token or wSOL account the output landed in
+    #[account(
+        mut,
+        constraint = output_account.key() == swap_session.output_account @ ControllerError::InvalidTokenAccount
+    )]
+    pub output_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = keeper,
+        seeds = [b"swap_session", state.key().as_ref()],
+        bump = swap_session.bump,
+        constraint = swap_session.keeper == keeper.key() @ ControllerError::UnauthorizedKeeper
+    )]
+    pub swap_session: Account<'info, SwapSession>,
+
+    #[account(
+        mut,
+        seeds = [b"keeper", state.key().as_ref(), keeper.key().as_ref()],
+        bump = keeper_account.bump
+    )]
+    pub keeper_account: Account<'info, KeeperAccount>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+/// Settle a guarded swap: the output account (treasury token or wSOL account) must have
+/// gained at least the session's minimum output; wSOL is then unwrapped into the SOL vault.
+/// A session `begin_swap` left empty after a breaker trip is closed without settlement.
+pub fn end_swap_handler(ctx: Context<EndSwap>) -> Result<()> {
+    let session = &ctx.accounts.swap_session;
+    if session.amount_in == 0 {
+        msg!("Guarded swap closed without settlement: circuit breaker tripped");
+        return Ok(());
+    }
+
+    let received = ctx
+        .accounts
+        .output_account
+        .amount
+        .saturating_sub(session.balance_before);
+    require!(
+        received >= session.min_amount_out,
+        ControllerError::SlippageExceeded
+    );
+
+    // Volume is recorded at the lamports begin_swap checked; `received` only measures slippage
+    let (direction, amount_in, lamports, dex_program, expected_out) = (
+        session.direction,
+        session.amount_in,
+        session.lamports,
+        session.dex_program,
+        session.expected_out,
+    );
+    let now = Clock::get()?.unix_timestamp;
+    record_swap(
+        &mut ctx.accounts.state,
+        &mut ctx.accounts.keeper_account,
+        lamports,
+        now,
+    )?;
+    // Pausing on a slippage spike still settles this swap
+    let slippage_bps = record_realized_slippage(&mut ctx.accounts.state, expected_out, received, now);
+
+    // Close the wSOL account into the SOL vault, unwrapping the output with its rent
+    if direction == SwapDirection::TokenToSol {
+        let token_program = ctx
+            .accounts
+            .token_program
+            .as_ref()
+            .ok_or(ControllerError::InvalidTokenAccount)?;
+
+        let state_key = ctx.accounts.state.key();
+        let seeds = &[
+            b"treasury_sol".as_ref(),
+            state_key.as_ref(),
+            &[ctx.accounts.state.sol_vault_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        token::close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.output_account.to_account_info(),
+                destination: ctx.accounts.sol_vault.to_account_info(),
+                authority: ctx.accounts.sol_vault.to_account_info(),
+            },
+            signer,
+        ))?;
+    }
+
+    msg!(
+        "Guarded swap settled: {:?} {} in -> {} out ({} bps below quote) via DEX {}",
+        direction,
+        amount_in,
+        received,
+        slippage_bps,
+        dex_program
+    );
+
+    Ok(())
+}
+
+/// Require the next instruction to invoke `dex_program`, the one after to be this
+/// program's `end_swap` for `state`'s session, and every other instruction to use
+/// this program, a built-in approved program or one the admin approved.
+fn verify_swap_composition(
+    instructions_sysvar: &AccountInfo,
+    state_key: &Pubkey,
+    state: &ControllerState,
+    dex_program: &Pubkey,
+    swap_session: &Pubkey,
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions_sysvar)? as usize;
+    let num_instructions = {
+        let data = instructions_sysvar.try_borrow_data()?;
+        u16::from_le_bytes([data[0], data[1]]) as usize
+    };
+    require!(
+        current_index + 2 < num_instructions,
+        ControllerError::InvalidSwapComposition
+    );
+
+    for index in 0..num_instructions {
+        let ix = load_instruction_at_checked(index, instructions_sysvar)?;
+        if index == current_index + 1 {
+            require!(
+                ix.program_id == *dex_program,
+                ControllerError::InvalidSwapComposition
+            );
+        } else if index == current_index + 2 {
+            let has_account = |key: &Pubkey| ix.accounts.iter().any(|meta| meta.pubkey == *key);
+            require!(
+                ix.program_id == crate::ID
+                    && ix.data.starts_with(&crate::instruction::EndSwap::DISCRIMINATOR)
+                    && has_account(state_key)
+                    && has_account(swap_session),
+                ControllerError::InvalidSwapComposition
+            );
+        } else {
+            // The DEX may only run in the guarded slot, so nothing can trade around it
+            require!(
+                is_approved_program(state, &ix.program_id),
+                ControllerError::UnapprovedProgram
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Programs allowed outside the guarded slot: this program, system, token, associated
+/// token and compute budget, plus the admin-approved set
+fn is_approved_program(state: &ControllerState, program_id: &Pubkey) -> bool {
+    *program_id == crate::ID
+        || *program_id == system_program::ID
+        || *program_id == token::ID
+        || *program_id == ASSOCIATED_TOKEN_PROGRAM_ID
+        || *program_id == COMPUTE_BUDGET_PROGRAM_ID
+        || state.find_approved_program(program_id).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+    use anchor_lang::solana_program::sysvar::instructions::{
+        construct_instructions_data, store_current_index, BorrowedAccountMeta, BorrowedInstruction,
+    };
+    use anchor_lang::{InstructionData, ToAccountMetas};
+
+    /// Index of `begin_swap` in `token_to_sol`
+    const BEGIN_INDEX: u16 = 2;
+
+    struct Keys {
+        state: Pubkey,
+        sol_vault: Pubkey,
+        treasury_token_account: Pubkey,
+        treasury_wsol_account: Pubkey,
+        keeper_token_account: Pubkey,
+        swap_session: Pubkey,
+        keeper_account: Pubkey,
+        keeper: Pubkey,
+        dex_program: Pubkey,
+    }
+
+    fn keys() -> Keys {
+        Keys {
+            state: Pubkey::new_unique(),
+            sol_vault: Pubkey::new_unique(),
+            treasury_token_account: Pubkey::new_unique(),
+            treasury_wsol_account: Pubkey::new_unique(),
+            keeper_token_account: Pubkey::new_unique(),
+            swap_session: Pubkey::new_unique(),
+            keeper_account: Pubkey::new_unique(),
+            keeper: Pubkey::new_unique(),
+            dex_program: Pubkey::new_unique(),
+        }
+    }
+
+    fn state() -> ControllerState {
+        ControllerState::deserialize(&mut &vec![0u8; ControllerState::LEN][8..]).unwrap()
+    }
+
+    fn end_swap(keys: &Keys, swap_session: Pubkey) -> Instruction {
+        Instruction {
+            program_id: crate::ID,
+            accounts: crate::accounts::EndSwap {
+                state: keys.state,
+                sol_vault: keys.sol_vault,
+                output_account: keys.treasury_wsol_account,
+                swap_session,
+                keeper_account: keys.keeper_account,
+                keeper: keys.keeper,
+                token_program: Some(token::ID),
+            }
+            .to_account_metas(None),
+            data: crate::instruction::EndSwap {}.data(),
+        }
+    }
+
+    /// Token-to-SOL guarded swap as a keeper sends it: set the compute limit, create the
+    /// SOL vault's wSOL account, begin_swap, the DEX swap paying wSOL into it, end_swap
+    fn token_to_sol(keys: &Keys) -> Vec<Instruction> {
+        let set_compute_unit_limit = Instruction {
+            program_id: COMPUTE_BUDGET_PROGRAM_ID,
+            accounts: vec![],
+            data: [&[2u8][..], &400_000u32.to_le_bytes()].concat(),
+        };
+        let create_wsol_account = Instruction {
+            program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(keys.keeper, true),
+                AccountMeta::new(keys.treasury_wsol_account, false),
+                AccountMeta::new_readonly(keys.sol_vault, false),
+                AccountMeta::new_readonly(token::spl_token::native_mint::ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(token::ID, false),
+            ],
+            // CreateIdempotent
+            data: vec![1],
+        };
+        let begin_swap = Instruction {
+            program_id: crate::ID,
+            accounts: crate::accounts::BeginSwap {
+                state: keys.state,
+                sol_vault: keys.sol_vault,
+                treasury_token_account: keys.treasury_token_account,
+                keeper_token_account: Some(keys.keeper_token_account),
+                treasury_wsol_account: Some(keys.treasury_wsol_account),
+                swap_session: keys.swap_session,
+                keeper_account: keys.keeper_account,
+                keeper: keys.keeper,
+                dex_program: keys.dex_program,
+                oracle: Some(Pubkey::new_unique()),
+                pool_config: None,
+                pool: None,
+                execution_plan: None,
+                instructions_sysvar: sysvar_instructions::ID,
+                token_program: Some(token::ID),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::BeginSwap {
+                direction: SwapDirection::TokenToSol,
+                amount_in: 1_000_000,
+                min_amount_out: 400_000,
+            }
+            .data(),
+        };
+        let dex_swap = Instruction {
+            program_id: keys.dex_program,
+            accounts: vec![
+                AccountMeta::new_readonly(keys.keeper, true),
+                AccountMeta::new(keys.keeper_token_account, false),
+                AccountMeta::new(keys.treasury_wsol_account, false),
+            ],
+            data: vec![9],
+        };
+        vec![
+            set_compute_unit_limit,
+            create_wsol_account,
+            begin_swap,
+            dex_swap,
+            end_swap(keys, keys.swap_session),
+        ]
+    }
+
+    /// Run `verify_swap_composition` as `begin_swap` at `BEGIN_INDEX` of `instructions`
+    fn verify(instructions: &[Instruction], keys: &Keys, state: &ControllerState) -> Result<()> {
+        let borrowed: Vec<BorrowedInstruction> = instructions
+            .iter()
+            .map(|ix| BorrowedInstruction {
+                program_id: &ix.program_id,
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .map(|meta| BorrowedAccountMeta {
+                        pubkey: &meta.pubkey,
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable,
+                    })
+                    .collect(),
+                data: &ix.data,
+            })
+            .collect();
+        let mut data = construct_instructions_data(&borrowed);
+        store_current_index(&mut data, BEGIN_INDEX);
+
+        let (key, owner) = (sysvar_instructions::ID, anchor_lang::solana_program::sysvar::ID);
+        let mut lamports = 0;
+        let sysvar = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        verify_swap_composition(&sysvar, &keys.state, state, &keys.dex_program, &keys.swap_session)
+    }
+
+    #[test]
+    fn token_to_sol_sequence_passes() {
+        let keys = keys();
+        assert!(verify(&token_to_sol(&keys), &keys, &state()).is_ok());
+    }
+
+    #[test]
+    fn end_swap_must_follow_the_dex_swap() {
+        let keys = keys();
+        let mut instructions = token_to_sol(&keys);
+        instructions.pop();
+        let res = verify(&instructions, &keys, &state());
+        assert_eq!(res.unwrap_err(), ControllerError::InvalidSwapComposition.into());
+
+        // The DEX swap must directly follow begin_swap
+        let mut instructions = token_to_sol(&keys);
+        instructions.swap(3, 4);
+        let res = verify(&instructions, &keys, &state());
+        assert_eq!(res.unwrap_err(), ControllerError::InvalidSwapComposition.into());
+    }
+
+    #[test]
+    fn end_swap_must_name_this_session() {
+        let keys = keys();
+        let mut instructions = token_to_sol(&keys);
+        instructions[4] = end_swap(&keys, Pubkey::new_unique());
+        let res = verify(&instructions, &keys, &state());
+        assert_eq!(res.unwrap_err(), ControllerError::InvalidSwapComposition.into());
+    }
+
+    #[test]
+    fn dex_only_runs_in_the_guarded_slot() {
+        let keys = keys();
+        let mut instructions = token_to_sol(&keys);
+        let dex_swap = instructions[3].clone();
+        instructions.push(dex_swap);
+        let res = verify(&instructions, &keys, &state());
+        assert_eq!(res.unwrap_err(), ControllerError::UnapprovedProgram.into());
+    }
+
+    #[test]
+    fn other_programs_need_approval() {
+        let keys = keys();
+        let program_id = Pubkey::new_unique();
+        let mut instructions = token_to_sol(&keys);
+        // Replace the compute budget instruction, keeping begin_swap at BEGIN_INDEX
+        instructions[0] = Instruction { program_id, accounts: vec![], data: vec![] };
+
+        let mut state = state();
+        let res = verify(&instructions, &keys, &state);
+        assert_eq!(res.unwrap_err(), ControllerError::UnapprovedProgram.into());
+
+        state.approved_programs[0] = program_id;
+        state.num_approved_programs = 1;
+        assert!(verify(&instructions, &keys, &state).is_ok());
+    }
+}
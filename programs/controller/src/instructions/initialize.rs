@@ -1,7 +1,10 @@
 use anchor_lang::prelude::*;
-use crate::state::{ControllerState, MAX_KEEPERS, MAX_DEX_PROGRAMS};
+use anchor_lang::solana_program::{bpf_loader_upgradeable, program_option::COption};
+use anchor_spl::token::Mint;
+use crate::state::{BreakerWindow, ControllerState, KeeperAccount, VolumeTracker, MAX_APPROVED_PROGRAMS, MAX_DEX_PROGRAMS, MAX_SLIPPAGE_BPS, PERMISSION_ALL};
 use crate::error::ControllerError;
-use crate::ControllerConfig;
+use crate::instructions::keeper::init_keeper_account;
+use crate::{CircuitBreakerConfig, ControllerConfig, DexEntry, KeeperScope, OracleConfig};
 
 #[derive(Accounts)]
 #[instruction(bump: u8)]
@@ -10,41 +13,68 @@ pub struct Initialize<'info> {
         init,
         payer = admin,
         space = ControllerState::LEN,
-        seeds = [b"controller_state"],
+        seeds = [b"controller_state", mint.key().as_ref()],
         bump
     )]
     pub state: Account<'info, ControllerState>,
 
+    /// Flywheel token mint this instance manages
+    pub mint: Account<'info, Mint>,
+
     /// CHECK: SOL vault PDA
     #[account(
-        seeds = [b"treasury_sol"],
+        seeds = [b"treasury_sol", state.key().as_ref()],
         bump
     )]
     pub sol_vault: AccountInfo<'info>,
 
+    /// Admin is registered as the initial keeper
+    #[account(
+        init,
+        payer = admin,
+        space = KeeperAccount::LEN,
+        seeds = [b"keeper", state.key().as_ref(), admin.key().as_ref()],
+        bump
+    )]
+    pub admin_keeper: Account<'info, KeeperAccount>,
+
     #[account(mut)]
     pub admin: Signer<'info>,
 
+    /// Mint authority of `mint`, or the program upgrade authority for mints whose authority is revoked
+    pub authority: Signer<'info>,
+
+    /// This program's program data; required when `authority` is not the mint authority
+    pub program_data: Option<Account<'info, ProgramData>>,
+
     pub system_program: Program<'info, System>,
 }
 
 pub fn handler(ctx: Context<Initialize>, bump: u8, config: ControllerConfig) -> Result<()> {
     require!(config.weights.validate(), ControllerError::InvalidWeights);
+    require!(config.volume_limits.validate(), ControllerError::InvalidVolumeLimits);
+    require!(config.min_swap_interval_seconds >= 0, ControllerError::InvalidAmount);
+    require!(config.max_slippage_bps <= MAX_SLIPPAGE_BPS, ControllerError::InvalidSlippage);
+    authorize_creator(
+        &ctx.accounts.mint,
+        &ctx.accounts.authority.key(),
+        ctx.accounts.program_data.as_ref(),
+    )?;
 
     let state = &mut ctx.accounts.state;
     
     state.admin = ctx.accounts.admin.key();
+    state.mint = ctx.accounts.mint.key();
     state.paused = false;
     state.weights = config.weights;
     state.max_slippage_bps = config.max_slippage_bps;
     state.max_trade_lamports = config.max_trade_lamports;
-    state.max_trades_per_day = config.max_trades_per_day;
-    state.trades_today = 0;
-    state.current_day = 0;
-    state.keepers = [Pubkey::default(); MAX_KEEPERS];
-    state.keepers[0] = ctx.accounts.admin.key(); // Admin is initial keeper
+    state.max_trades_per_period = config.max_trades_per_period;
+    state.trades_this_period = 0;
+    state.volume_limits = config.volume_limits;
+    state.volume = VolumeTracker::default();
     state.num_keepers = 1;
-    state.allowed_dex_programs = [Pubkey::default(); MAX_DEX_PROGRAMS];
+    state.allowed_dexes = [DexEntry::default(); MAX_DEX_PROGRAMS];
     state.num_dex_programs = 0;
     state.sol_vault_bump = ctx.bumps.sol_vault;
     state.bump = bump;
@@ -53,8 +83,36 @@ pub fn handler(ctx: Context<Initialize>, bump: u8, config: ControllerConfig) ->
     state.total_routed_burn = 0;
     state.total_routed_auto_lp = 0;
     state.total_tokens_burned = 0;
+    state.min_swap_interval_seconds = config.min_swap_interval_seconds;
+    state.last_swap_at = 0;
+    state.oracle = OracleConfig::default();
+    state.breaker = CircuitBreakerConfig::default();
+    state.breaker_window = BreakerWindow::default();
+    state.trip_reason = None;
+    state.swap_guard_enabled = false;
+    state.approved_programs = [Pubkey::default(); MAX_APPROVED_PROGRAMS];
+    state.num_approved_programs = 0;
+    state.active_plan = Pubkey::default();
+
+    init_keeper_account(
+        &mut ctx.accounts.admin_keeper,
+        ctx.accounts.admin.key(),
+        KeeperScope {
+            permissions: PERMISSION_ALL,
+            max_swap_lamports: 0,
+            max_route_lamports: 0,
+            max_period_lamports: 0,
+            max_window_lamports: 0,
+        },
+        ctx.bumps.admin_keeper,
+        Clock::get()?.unix_timestamp,
+    );
 
-    msg!("Controller initialized with admin: {}", state.admin);
+    msg!(
+        "Controller for mint {} initialized with admin: {}",
+        state.mint,
+        state.admin
+    );
     msg!(
         "Weights: rewards={}%, buyback={}%, burn={}%, auto_lp={}%",
         config.weights.rewards_pct,
@@ -66,4 +124,23 @@ pub fn handler(ctx: Context<Initialize>, bump: u8, config: ControllerConfig) ->
     Ok(())
 }
 
+/// Only the mint authority or the program upgrade authority may create a mint's
+/// controller, so nobody else can squat its PDA and become admin
+fn authorize_creator(
+    mint: &Mint,
+    authority: &Pubkey,
+    program_data: Option<&Account<ProgramData>>,
+) -> Result<()> {
+    if mint.mint_authority == COption::Some(*authority) {
+        return Ok(());
+    }
 
+    let program_data = program_data.ok_or(ControllerError::UnauthorizedAdmin)?;
+    let (expected, _) = Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::ID);
+    require!(
+        program_data.key() == expected
+            && program_data.upgrade_authority_address == Some(*authority),
+        ControllerError::UnauthorizedAdmin
+    );
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+use crate::state::{ControllerState, KeeperAccount, KeeperStatus, VolumeTracker};
+use crate::error::ControllerError;
+use crate::KeeperScope;
+
+#[derive(Accounts)]
+#[instruction(keeper: Pubkey)]
+pub struct AddKeeper<'info> {
+    #[account(
+        mut,
+        seeds = [b"controller_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ ControllerError::UnauthorizedAdmin
+    )]
+    pub state: Account<'info, ControllerState>,
+
+    /// Fails to initialize if the keeper is already registered
+    #[account(
+        init,
+        payer = admin,
+        space = KeeperAccount::LEN,
+        seeds = [b"keeper", state.key().as_ref(), keeper.as_ref()],
+        bump
+    )]
+    pub keeper_account: Account<'info, KeeperAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_keeper_handler(ctx: Context<AddKeeper>, keeper: Pubkey, scope: KeeperScope) -> Result<()> {
+    require!(scope.validate(), ControllerError::InvalidPermissions);
+
+    let keeper_account = &mut ctx.accounts.keeper_account;
+    init_keeper_account(
+        keeper_account,
+        keeper,
+        scope,
+        ctx.bumps.keeper_account,
+        Clock::get()?.unix_timestamp,
+    );
+
+    let state = &mut ctx.accounts.state;
+    state.num_keepers = state
+        .num_keepers
+        .checked_add(1)
+        .ok_or(ControllerError::MathOverflow)?;
+
+    msg!(
+        "Keeper added: {} (permissions {:#06x}, max swap {}, max route {})",
+        keeper,
+        scope.permissions,
+        scope.max_swap_lamports,
+        scope.max_route_lamports
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveKeeper<'info> {
+    #[account(
+        mut,
+        seeds = [b"controller_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ ControllerError::UnauthorizedAdmin
+    )]
+    pub state: Account<'info, ControllerState>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"keeper", state.key().as_ref(), keeper_account.keeper.as_ref()],
+        bump = keeper_account.bump
+    )]
+    pub keeper_account: Account<'info, KeeperAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn remove_keeper_handler(ctx: Context<RemoveKeeper>) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    state.num_keepers = state
+        .num_keepers
+        .checked_sub(1)
+        .ok_or(ControllerError::MathOverflow)?;
+
+    msg!("Keeper removed: {}", ctx.accounts.keeper_account.keeper);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetKeeperActive<'info> {
+    #[account(
+        seeds = [b"controller_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ ControllerError::UnauthorizedAdmin
+    )]
+    pub state: Account<'info, ControllerState>,
+
+    #[account(
+        mut,
+        seeds = [b"keeper", state.key().as_ref(), keeper_account.keeper.as_ref()],
+        bump = keeper_account.bump
+    )]
+    pub keeper_account: Account<'info, KeeperAccount>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn set_keeper_active_handler(ctx: Context<SetKeeperActive>, active: bool) -> Result<()> {
+    let keeper_account = &mut ctx.accounts.keeper_account;
+    keeper_account.status = if active {
+        KeeperStatus::Active
+    } else {
+        KeeperStatus::Suspended
+    };
+
+    msg!("Keeper {} active set to: {}", keeper_account.keeper, active);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetKeeperScope<'info> {
+    #[account(
+        seeds = [b"controller_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ ControllerError::UnauthorizedAdmin
+    )]
+    pub state: Account<'info, ControllerState>,
+
+    #[account(
+        mut,
+        seeds = [b"keeper", state.key().as_ref(), keeper_account.keeper.as_ref()],
+        bump = keeper_account.bump
+    )]
+    pub keeper_account: Account<'info, KeeperAccount>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn set_keeper_scope_handler(ctx: Context<SetKeeperScope>, scope: KeeperScope) -> Result<()> {
+    require!(scope.validate(), ControllerError::InvalidPermissions);
+
+    let keeper_account = &mut ctx.accounts.keeper_account;
+    keeper_account.permissions = scope.permissions;
+    keeper_account.max_swap_lamports = scope.max_swap_lamports;
+    keeper_account.max_route_lamports = scope.max_route_lamports;
+    keeper_account.max_period_lamports = scope.max_period_lamports;
+    keeper_account.max_window_lamports = scope.max_window_lamports;
+
+    msg!(
+        "Keeper {} scope updated: permissions {:#06x}, max swap {}, max route {}",
+        keeper_account.keeper,
+        scope.permissions,
+        scope.max_swap_lamports,
+        scope.max_route_lamports
+    );
+
+    Ok(())
+}
+
+/// Populate a freshly created keeper account
+pub fn init_keeper_account(
+    keeper_account: &mut KeeperAccount,
+    keeper: Pubkey,
+    scope: KeeperScope,
+    bump: u8,
+    now: i64,
+) {
+    keeper_account.keeper = keeper;
+    keeper_account.status = KeeperStatus::Active;
+    keeper_account.permissions = scope.permissions;
+    keeper_account.max_swap_lamports = scope.max_swap_lamports;
+    keeper_account.max_route_lamports = scope.max_route_lamports;
+    keeper_account.max_period_lamports = scope.max_period_lamports;
+    keeper_account.max_window_lamports = scope.max_window_lamports;
+    keeper_account.volume = VolumeTracker::default();
+    keeper_account.swaps_executed = 0;
+    keeper_account.routes_executed = 0;
+    keeper_account.lamports_swapped = 0;
+    keeper_account.lamports_routed = 0;
+    keeper_account.registered_at = now;
+    keeper_account.last_active_at = 0;
+    keeper_account.bump = bump;
+}
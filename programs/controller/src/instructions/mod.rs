@@ -1,15 +1,25 @@
 pub mod initialize;
 pub mod config;
 pub mod swap;
+pub mod guard;
+pub mod plan;
+pub mod pool;
 pub mod route;
 pub mod treasury;
 pub mod admin;
+pub mod breaker;
+pub mod keeper;
 
 pub use initialize::*;
 pub use config::*;
 pub use swap::*;
+pub use guard::*;
+pub use plan::*;
+pub use pool::*;
 pub use route::*;
 pub use treasury::*;
 pub use admin::*;
+pub use breaker::*;
+pub use keeper::*;
 
 
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use crate::state::{ControllerState, ExecutionPlan};
+use crate::error::ControllerError;
+use crate::ExecutionPlanParams;
+
+#[derive(Accounts)]
+#[instruction(plan_id: u64)]
+pub struct CreateExecutionPlan<'info> {
+    #[account(
+        mut,
+        seeds = [b"controller_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ ControllerError::UnauthorizedAdmin
+    )]
+    pub state: Account<'info, ControllerState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = ExecutionPlan::LEN,
+        seeds = [b"execution_plan", state.key().as_ref(), &plan_id.to_le_bytes()],
+        bump
+    )]
+    pub execution_plan: Account<'info, ExecutionPlan>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_handler(
+    ctx: Context<CreateExecutionPlan>,
+    plan_id: u64,
+    params: ExecutionPlanParams,
+) -> Result<()> {
+    require!(params.validate(), ControllerError::InvalidExecutionPlan);
+    require!(
+        params.expiry > Clock::get()?.unix_timestamp,
+        ControllerError::InvalidExecutionPlan
+    );
+    require!(
+        ctx.accounts.state.active_plan == Pubkey::default(),
+        ControllerError::ExecutionPlanActive
+    );
+
+    let plan = &mut ctx.accounts.execution_plan;
+    plan.plan_id = plan_id;
+    plan.total_amount = params.total_amount;
+    plan.chunk_size = params.chunk_size;
+    plan.min_interval_seconds = params.min_interval_seconds;
+    plan.expiry = params.expiry;
+    plan.filled_amount = 0;
+    plan.chunks_executed = 0;
+    plan.last_chunk_at = 0;
+    plan.bump = ctx.bumps.execution_plan;
+    ctx.accounts.state.active_plan = plan.key();
+
+    msg!(
+        "Execution plan {} created: {} lamports in chunks of {} every {}s until {}",
+        plan_id,
+        params.total_amount,
+        params.chunk_size,
+        params.min_interval_seconds,
+        params.expiry
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseExecutionPlan<'info> {
+    #[account(
+        mut,
+        seeds = [b"controller_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ ControllerError::UnauthorizedAdmin
+    )]
+    pub state: Account<'info, ControllerState>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"execution_plan", state.key().as_ref(), &execution_plan.plan_id.to_le_bytes()],
+        bump = execution_plan.bump
+    )]
+    pub execution_plan: Account<'info, ExecutionPlan>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn close_handler(ctx: Context<CloseExecutionPlan>) -> Result<()> {
+    let plan = &ctx.accounts.execution_plan;
+    let state = &mut ctx.accounts.state;
+    if state.active_plan == plan.key() {
+        state.active_plan = Pubkey::default();
+    }
+
+    msg!(
+        "Execution plan {} closed after {} chunks ({}/{} lamports filled)",
+        plan.plan_id,
+        plan.chunks_executed,
+        plan.filled_amount,
+        plan.total_amount
+    );
+
+    Ok(())
+}
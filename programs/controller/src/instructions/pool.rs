@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use crate::state::{ControllerState, PoolConfig};
+use crate::error::ControllerError;
+use crate::PoolParams;
+
+#[derive(Accounts)]
+pub struct AddPool<'info> {
+    #[account(
+        seeds = [b"controller_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ ControllerError::UnauthorizedAdmin
+    )]
+    pub state: Account<'info, ControllerState>,
+
+    /// CHECK: Constant-product pool account; reserves are read at configured offsets
+    pub pool: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = PoolConfig::LEN,
+        seeds = [b"pool", state.key().as_ref(), pool.key().as_ref()],
+        bump
+    )]
+    pub pool_config: Account<'info, PoolConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_pool_handler(ctx: Context<AddPool>, params: PoolParams) -> Result<()> {
+    require!(params.validate(), ControllerError::InvalidPoolConfig);
+    require!(
+        ctx.accounts.pool.owner == &params.dex_program,
+        ControllerError::InvalidPoolAccount
+    );
+    require!(
+        ctx.accounts.state.find_dex(&params.dex_program).is_some(),
+        ControllerError::DexNotAllowed
+    );
+
+    let pool_config = &mut ctx.accounts.pool_config;
+    pool_config.pool = ctx.accounts.pool.key();
+    pool_config.bump = ctx.bumps.pool_config;
+    pool_config.apply(&params);
+
+    msg!(
+        "Pool {} added for DEX {} and mint {} (fee {} bps, reference price {}, max deviation {} bps)",
+        pool_config.pool,
+        params.dex_program,
+        params.token_mint,
+        params.fee_bps,
+        params.reference_price,
+        params.max_deviation_bps
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdatePool<'info> {
+    #[account(
+        seeds = [b"controller_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ ControllerError::UnauthorizedAdmin
+    )]
+    pub state: Account<'info, ControllerState>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", state.key().as_ref(), pool_config.pool.as_ref()],
+        bump = pool_config.bump
+    )]
+    pub pool_config: Account<'info, PoolConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn update_pool_handler(ctx: Context<UpdatePool>, params: PoolParams) -> Result<()> {
+    require!(params.validate(), ControllerError::InvalidPoolConfig);
+    require!(
+        ctx.accounts.pool_config.dex_program == params.dex_program,
+        ControllerError::InvalidPoolConfig
+    );
+
+    let pool_config = &mut ctx.accounts.pool_config;
+    pool_config.apply(&params);
+
+    msg!(
+        "Pool {} updated (fee {} bps, reference price {}, max deviation {} bps)",
+        pool_config.pool,
+        params.fee_bps,
+        params.reference_price,
+        params.max_deviation_bps
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemovePool<'info> {
+    #[account(
+        seeds = [b"controller_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ ControllerError::UnauthorizedAdmin
+    )]
+    pub state: Account<'info, ControllerState>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"pool", state.key().as_ref(), pool_config.pool.as_ref()],
+        bump = pool_config.bump
+    )]
+    pub pool_config: Account<'info, PoolConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn remove_pool_handler(ctx: Context<RemovePool>) -> Result<()> {
+    msg!("Pool {} removed", ctx.accounts.pool_config.pool);
+
+    Ok(())
+}
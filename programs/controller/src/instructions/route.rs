@@ -1,23 +1,23 @@
 use anchor_lang::prelude::*;
-use crate::state::ControllerState;
+use crate::state::{ControllerState, KeeperAccount};
 use crate::error::ControllerError;
+use crate::instructions::breaker::record_outflow;
 use crate::RouteAction;
 
 #[derive(Accounts)]
 pub struct RouteFunds<'info> {
     #[account(
         mut,
-        seeds = [b"controller_state"],
+        seeds = [b"controller_state", state.mint.as_ref()],
         bump = state.bump,
-        constraint = !state.paused @ ControllerError::Paused,
-        constraint = state.is_keeper(&keeper.key()) @ ControllerError::UnauthorizedKeeper
+        constraint = !state.paused @ ControllerError::Paused
     )]
     pub state: Account<'info, ControllerState>,
 
     /// CHECK: Treasury SOL vault
     #[account(
         mut,
-        seeds = [b"treasury_sol"],
+        seeds = [b"treasury_sol", state.key().as_ref()],
         bump = state.sol_vault_bump
     )]
     pub sol_vault: AccountInfo<'info>,
@@ -26,6 +26,14 @@ pub struct RouteFunds<'info> {
     #[account(mut)]
     pub destination: AccountInfo<'info>,
 
+    #[account(
+        mut,
+        seeds = [b"keeper", state.key().as_ref(), keeper.key().as_ref()],
+        bump = keeper_account.bump,
+        constraint = keeper_account.is_active() @ ControllerError::KeeperSuspended
+    )]
+    pub keeper_account: Account<'info, KeeperAccount>,
+
     #[account(mut)]
     pub keeper: Signer<'info>,
 
@@ -35,6 +43,16 @@ pub struct RouteFunds<'info> {
 pub fn handler(ctx: Context<RouteFunds>, action: RouteAction, amount: u64) -> Result<()> {
     require!(amount > 0, ControllerError::ZeroAmount);
 
+    let keeper_account = &ctx.accounts.keeper_account;
+    require!(
+        keeper_account.has_permission(action.permission()),
+        ControllerError::MissingPermission
+    );
+    require!(
+        KeeperAccount::within_cap(keeper_account.max_route_lamports, amount),
+        ControllerError::KeeperLimitExceeded
+    );
+
     let state = &mut ctx.accounts.state;
     let vault_balance = ctx.accounts.sol_vault.lamports();
     
@@ -43,6 +61,11 @@ pub fn handler(ctx: Context<RouteFunds>, action: RouteAction, amount: u64) -> Re
         ControllerError::InsufficientBalance
     );
 
+    // Circuit breaker: pause instead of routing if the window's outflow limit would be crossed
+    if !record_outflow(state, vault_balance, amount, Clock::get()?.unix_timestamp) {
+        return Ok(());
+    }
+
     // Transfer SOL from vault to destination
     **ctx.accounts.sol_vault.try_borrow_mut_lamports()? -= amount;
     **ctx.accounts.destination.try_borrow_mut_lamports()? += amount;
@@ -79,6 +102,17 @@ pub fn handler(ctx: Context<RouteFunds>, action: RouteAction, amount: u64) -> Re
         }
     }
 
+    let keeper_account = &mut ctx.accounts.keeper_account;
+    keeper_account.routes_executed = keeper_account
+        .routes_executed
+        .checked_add(1)
+        .ok_or(ControllerError::MathOverflow)?;
+    keeper_account.lamports_routed = keeper_account
+        .lamports_routed
+        .checked_add(amount)
+        .ok_or(ControllerError::MathOverflow)?;
+    keeper_account.last_active_at = Clock::get()?.unix_timestamp;
+
     Ok(())
 }
 
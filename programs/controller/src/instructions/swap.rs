@@ -1,73 +1,168 @@
 use anchor_lang::prelude::*;
-use crate::state::ControllerState;
+use anchor_spl::token::TokenAccount;
+use crate::state::{ControllerState, ExecutionPlan, KeeperAccount, PoolConfig, PERMISSION_SWAP};
 use crate::error::ControllerError;
+use crate::oracle::{load_price, min_out_with_slippage};
+use crate::pool::PoolReserves;
+use crate::instructions::breaker::record_outflow;
+use crate::SwapDirection;
+
+/// Direction and amounts of a swap, shared by `execute_swap` and `begin_swap`
+#[derive(Clone, Copy, Debug)]
+pub struct SwapRequest {
+    pub direction: SwapDirection,
+    /// Input amount: lamports for SOL-to-token, token base units for token-to-SOL
+    pub amount_in: u64,
+    /// Minimum output: token base units for SOL-to-token, lamports for token-to-SOL
+    pub min_amount_out: u64,
+    /// Output of `amount_in` quoted by `check_price_bounds`; zero until checked
+    pub expected_out: u64,
+}
+
+impl SwapRequest {
+    /// SOL side of the swap, which trade size, volume, plan and breaker limits are counted in.
+    /// For token-to-SOL this is the oracle or pool quote, not the keeper-chosen minimum.
+    pub fn lamports(&self) -> u64 {
+        match self.direction {
+            SwapDirection::SolToToken => self.amount_in,
+            SwapDirection::TokenToSol => self.expected_out,
+        }
+    }
+}
 
 #[derive(Accounts)]
 pub struct ExecuteSwap<'info> {
     #[account(
         mut,
-        seeds = [b"controller_state"],
+        seeds = [b"controller_state", state.mint.as_ref()],
         bump = state.bump,
-        constraint = !state.paused @ ControllerError::Paused,
-        constraint = state.is_keeper(&keeper.key()) @ ControllerError::UnauthorizedKeeper
+        constraint = !state.paused @ ControllerError::Paused
     )]
     pub state: Account<'info, ControllerState>,
 
     /// CHECK: Treasury SOL vault
     #[account(
         mut,
-        seeds = [b"treasury_sol"],
+        seeds = [b"treasury_sol", state.key().as_ref()],
         bump = state.sol_vault_bump
     )]
     pub sol_vault: AccountInfo<'info>,
 
+    #[account(
+        mut,
+        seeds = [b"keeper", state.key().as_ref(), keeper.key().as_ref()],
+        bump = keeper_account.bump,
+        constraint = keeper_account.is_active() @ ControllerError::KeeperSuspended,
+        constraint = keeper_account.has_permission(PERMISSION_SWAP) @ ControllerError::MissingPermission
+    )]
+    pub keeper_account: Account<'info, KeeperAccount>,
+
     #[account(mut)]
     pub keeper: Signer<'info>,
 
+    /// Treasury token account holding the input of a token-to-SOL swap
+    #[account(
+        constraint = treasury_token_account.owner == sol_vault.key() @ ControllerError::InvalidTokenAccount
+    )]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
     /// CHECK: DEX program to use for swap
     pub dex_program: AccountInfo<'info>,
 
+    /// CHECK: Price oracle, must be the configured price account; parsed in the handler
+    #[account(address = state.oracle.price_account @ ControllerError::OracleMismatch)]
+    pub oracle: Option<AccountInfo<'info>>,
+
+    /// Registered pool of the swap's DEX
+    #[account(
+        seeds = [b"pool", state.key().as_ref(), pool_config.pool.as_ref()],
+        bump = pool_config.bump,
+        constraint = pool_config.dex_program == dex_program.key() @ ControllerError::InvalidPoolConfig
+    )]
+    pub pool_config: Option<Account<'info, PoolConfig>>,
+
+    /// CHECK: Pool account named by `pool_config`; checked and parsed in the handler
+    pub pool: Option<AccountInfo<'info>>,
+
+    /// Active plan this swap fills a chunk of; required while one is open
+    #[account(
+        mut,
+        seeds = [b"execution_plan", state.key().as_ref(), &execution_plan.plan_id.to_le_bytes()],
+        bump = execution_plan.bump,
+        constraint = execution_plan.key() == state.active_plan @ ControllerError::ExecutionPlanRequired
+    )]
+    pub execution_plan: Option<Account<'info, ExecutionPlan>>,
+
     pub system_program: Program<'info, System>,
     // Additional accounts for DEX CPI would be passed as remaining_accounts
 }
 
 pub fn handler(
     ctx: Context<ExecuteSwap>,
+    direction: SwapDirection,
     amount_in: u64,
     min_amount_out: u64,
 ) -> Result<()> {
-    let state = &mut ctx.accounts.state;
-
-    // Validate trade size
+    // Guarded mode only allows the begin/end swap pair
     require!(
-        amount_in <= state.max_trade_lamports,
-        ControllerError::TradeSizeExceeded
+        !ctx.accounts.state.swap_guard_enabled,
+        ControllerError::SwapGuardRequired
     );
 
-    // Check daily limits
-    let current_day = Clock::get()?.unix_timestamp as u64 / 86400;
-    if current_day != state.current_day {
-        state.current_day = current_day;
-        state.trades_today = 0;
-    }
+    let mut request = SwapRequest {
+        direction,
+        amount_in,
+        min_amount_out,
+        expected_out: 0,
+    };
+    let now = Clock::get()?.unix_timestamp;
+    let dex_program = ctx.accounts.dex_program.key();
 
-    require!(
-        state.trades_today < state.max_trades_per_day,
-        ControllerError::DailyLimitExceeded
-    );
+    // Token-to-SOL swaps sell from a treasury token account; SOL-to-token buys the flywheel token
+    let vault_balance = ctx.accounts.sol_vault.lamports();
+    let (token_mint, source_balance) = match direction {
+        SwapDirection::SolToToken => (ctx.accounts.state.mint, vault_balance),
+        SwapDirection::TokenToSol => {
+            let token_account = ctx
+                .accounts
+                .treasury_token_account
+                .as_ref()
+                .ok_or(ControllerError::InvalidTokenAccount)?;
+            (token_account.mint, token_account.amount)
+        }
+    };
 
-    // Validate DEX program is allowed
-    require!(
-        state.is_allowed_dex(&ctx.accounts.dex_program.key()),
-        ControllerError::DexNotAllowed
-    );
+    request.expected_out = check_price_bounds(
+        &ctx.accounts.state,
+        &token_mint,
+        ctx.accounts.oracle.as_ref(),
+        ctx.accounts.pool_config.as_ref(),
+        ctx.accounts.pool.as_ref(),
+        &request,
+        now,
+    )?;
 
-    // Check vault has sufficient balance
-    let vault_balance = ctx.accounts.sol_vault.lamports();
+    if !check_swap_limits(
+        &mut ctx.accounts.state,
+        &mut ctx.accounts.keeper_account,
+        &dex_program,
+        vault_balance,
+        source_balance,
+        &request,
+        now,
+    )? {
+        return Ok(());
+    }
+    let lamports = request.lamports();
+
+    // Fill the next chunk of the active execution plan
     require!(
-        vault_balance >= amount_in,
-        ControllerError::InsufficientBalance
+        ctx.accounts.state.active_plan == Pubkey::default() || ctx.accounts.execution_plan.is_some(),
+        ControllerError::ExecutionPlanRequired
     );
+    if let Some(plan) = ctx.accounts.execution_plan.as_mut() {
+        fill_execution_plan(plan, lamports, now)?;
+    }
 
     // NOTE: Actual swap execution would be done via CPI to the DEX program
     // The remaining_accounts would contain all necessary DEX accounts
@@ -77,18 +172,237 @@ pub fn handler(
     // In production, you'd do:
     // 1. CPI to DEX swap instruction
     // 2. Verify output amount >= min_amount_out
-    // 3. Calculate actual slippage and verify <= max_slippage_bps
 
-    state.trades_today += 1;
+    record_swap(
+        &mut ctx.accounts.state,
+        &mut ctx.accounts.keeper_account,
+        lamports,
+        now,
+    )?;
+
+    match direction {
+        SwapDirection::SolToToken => msg!(
+            "Swap executed: {} lamports -> min {} tokens via DEX {}",
+            amount_in,
+            min_amount_out,
+            dex_program
+        ),
+        SwapDirection::TokenToSol => msg!(
+            "Swap executed: {} {} tokens -> min {} lamports via DEX {}",
+            amount_in,
+            token_mint,
+            min_amount_out,
+            dex_program
+        ),
+    }
+
+    Ok(())
+}
+
+/// Count `lamports` as the next chunk of `plan`, enforcing its expiry, chunk size and interval
+pub fn fill_execution_plan(plan: &mut ExecutionPlan, lamports: u64, now: i64) -> Result<()> {
+    require!(now <= plan.expiry, ControllerError::ExecutionPlanExpired);
+    require!(
+        lamports <= plan.chunk_size && lamports <= plan.remaining(),
+        ControllerError::ExecutionPlanExceeded
+    );
+    if plan.chunks_executed > 0 {
+        require!(
+            now >= plan.last_chunk_at.saturating_add(plan.min_interval_seconds),
+            ControllerError::SwapCooldown
+        );
+    }
+    plan.filled_amount = plan
+        .filled_amount
+        .checked_add(lamports)
+        .ok_or(ControllerError::MathOverflow)?;
+    plan.chunks_executed = plan
+        .chunks_executed
+        .checked_add(1)
+        .ok_or(ControllerError::MathOverflow)?;
+    plan.last_chunk_at = now;
 
     msg!(
-        "Swap executed: {} lamports -> min {} tokens via DEX {}",
-        amount_in,
-        min_amount_out,
-        ctx.accounts.dex_program.key()
+        "Execution plan {} chunk {}: {}/{} lamports filled",
+        plan.plan_id,
+        plan.chunks_executed,
+        plan.filled_amount,
+        plan.total_amount
     );
 
     Ok(())
 }
 
+/// Check a priced swap against trade size, cooldown, volume, DEX and source balance limits,
+/// then the circuit breaker. `source_balance` is the SOL vault or treasury token balance, per direction.
+/// Returns false if the circuit breaker tripped instead; the caller must then move no funds.
+pub fn check_swap_limits(
+    state: &mut Account<ControllerState>,
+    keeper_account: &mut KeeperAccount,
+    dex_program: &Pubkey,
+    vault_balance: u64,
+    source_balance: u64,
+    request: &SwapRequest,
+    now: i64,
+) -> Result<bool> {
+    let lamports = request.lamports();
+
+    // Validate trade size
+    require!(
+        lamports <= state.max_trade_lamports,
+        ControllerError::TradeSizeExceeded
+    );
+    require!(
+        KeeperAccount::within_cap(keeper_account.max_swap_lamports, lamports),
+        ControllerError::KeeperLimitExceeded
+    );
+
+    // Enforce spacing between swaps
+    require!(
+        now >= state.last_swap_at.saturating_add(state.min_swap_interval_seconds),
+        ControllerError::SwapCooldown
+    );
+
+    // Check trade count and volume limits for the current period and rolling window
+    let limits = state.volume_limits;
+    if state.volume.roll(now, &limits) {
+        state.trades_this_period = 0;
+    }
+
+    require!(
+        state.trades_this_period < state.max_trades_per_period,
+        ControllerError::TradeLimitExceeded
+    );
+    require!(
+        state.volume.allows(
+            lamports,
+            limits.max_period_lamports,
+            limits.max_window_lamports
+        ),
+        ControllerError::VolumeLimitExceeded
+    );
+
+    keeper_account.volume.roll(now, &limits);
+    require!(
+        keeper_account.volume.allows(
+            lamports,
+            keeper_account.max_period_lamports,
+            keeper_account.max_window_lamports
+        ),
+        ControllerError::KeeperLimitExceeded
+    );
+
+    // Validate DEX program is allowed and within its trade cap
+    let dex = state
+        .allowed_dex(dex_program)
+        .ok_or(ControllerError::DexNotAllowed)?;
+    require!(
+        dex.max_trade_lamports == 0 || lamports <= dex.max_trade_lamports,
+        ControllerError::TradeSizeExceeded
+    );
+
+    // Check the source vault has sufficient balance
+    require!(
+        source_balance >= request.amount_in,
+        ControllerError::InsufficientBalance
+    );
+
+    // Circuit breaker, last so only an otherwise valid swap can trip it: pause instead of
+    // swapping if the window's outflow limit would be crossed. Token sales count at their
+    // quoted lamport value.
+    Ok(record_outflow(state, vault_balance, lamports, now))
+}
+
+/// Bound the keeper's minimum output by the oracle price, a registered pool's reserves, or both.
+/// `token_mint` is the token side of the swap; the oracle only prices the flywheel mint.
+/// Returns the expected output, the higher quote when both sources apply.
+pub fn check_price_bounds(
+    state: &ControllerState,
+    token_mint: &Pubkey,
+    oracle: Option<&AccountInfo>,
+    pool_config: Option<&Account<PoolConfig>>,
+    pool: Option<&AccountInfo>,
+    request: &SwapRequest,
+    now: i64,
+) -> Result<u64> {
+    let (amount_in, min_amount_out) = (request.amount_in, request.min_amount_out);
+    let mut quoted: Option<u128> = None;
+    if state.oracle.is_configured() && *token_mint == state.mint {
+        let oracle = oracle.ok_or(ControllerError::OracleMismatch)?;
+        let price = load_price(oracle)?;
+        price.validate(now, &state.oracle)?;
+        let expected_out = match request.direction {
+            SwapDirection::SolToToken => price.quote(amount_in),
+            SwapDirection::TokenToSol => price.quote_inverse(amount_in),
+        }
+        .ok_or(ControllerError::MathOverflow)?;
+        require!(
+            min_amount_out as u128
+                >= min_out_with_slippage(expected_out, state.max_slippage_bps)
+                    .ok_or(ControllerError::MathOverflow)?,
+            ControllerError::SlippageExceeded
+        );
+        quoted = Some(quoted.map_or(expected_out, |q| q.max(expected_out)));
+    }
+
+    // Bound it by the constant-product output of the pool's current reserves
+    if let Some(pool_config) = pool_config {
+        let pool = pool.ok_or(ControllerError::InvalidPoolAccount)?;
+        require!(
+            pool.key() == pool_config.pool && pool.owner == &pool_config.dex_program,
+            ControllerError::InvalidPoolAccount
+        );
+        require!(
+            pool_config.token_mint == *token_mint,
+            ControllerError::InvalidPoolConfig
+        );
+        let reserves = PoolReserves::load(pool, pool_config)?;
+        reserves.check_deviation(pool_config)?;
+        let reserves = match request.direction {
+            SwapDirection::SolToToken => reserves,
+            SwapDirection::TokenToSol => reserves.reversed(),
+        };
+        let expected_out = reserves.quote(amount_in, pool_config.fee_bps);
+        require!(
+            min_amount_out as u128
+                >= min_out_with_slippage(expected_out, state.max_slippage_bps)
+                    .ok_or(ControllerError::MathOverflow)?,
+            ControllerError::SlippageExceeded
+        );
+        quoted = Some(quoted.map_or(expected_out, |q| q.max(expected_out)));
+    }
+    let expected_out = quoted.ok_or(ControllerError::PriceCheckRequired)?;
+
+    Ok(u64::try_from(expected_out).unwrap_or(u64::MAX))
+}
 
+/// Record a completed swap of `lamports` (its SOL side) in the global and keeper counters
+pub fn record_swap(
+    state: &mut ControllerState,
+    keeper_account: &mut KeeperAccount,
+    lamports: u64,
+    now: i64,
+) -> Result<()> {
+    state.trades_this_period += 1;
+    state.last_swap_at = now;
+    state
+        .volume
+        .record(lamports)
+        .ok_or(ControllerError::MathOverflow)?;
+
+    keeper_account
+        .volume
+        .record(lamports)
+        .ok_or(ControllerError::MathOverflow)?;
+    keeper_account.swaps_executed = keeper_account
+        .swaps_executed
+        .checked_add(1)
+        .ok_or(ControllerError::MathOverflow)?;
+    keeper_account.lamports_swapped = keeper_account
+        .lamports_swapped
+        .checked_add(lamports)
+        .ok_or(ControllerError::MathOverflow)?;
+    keeper_account.last_active_at = now;
+
+    Ok(())
+}
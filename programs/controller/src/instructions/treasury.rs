@@ -6,7 +6,7 @@ use crate::error::ControllerError;
 #[derive(Accounts)]
 pub struct FundTreasury<'info> {
     #[account(
-        seeds = [b"controller_state"],
+        seeds = [b"controller_state", state.mint.as_ref()],
         bump = state.bump
     )]
     pub state: Account<'info, ControllerState>,
@@ -14,7 +14,7 @@ pub struct FundTreasury<'info> {
     /// CHECK: Treasury SOL vault
     #[account(
         mut,
-        seeds = [b"treasury_sol"],
+        seeds = [b"treasury_sol", state.key().as_ref()],
         bump = state.sol_vault_bump
     )]
     pub sol_vault: AccountInfo<'info>,
@@ -47,7 +47,7 @@ pub fn fund_handler(ctx: Context<FundTreasury>, amount: u64) -> Result<()> {
 #[derive(Accounts)]
 pub struct WithdrawTreasury<'info> {
     #[account(
-        seeds = [b"controller_state"],
+        seeds = [b"controller_state", state.mint.as_ref()],
         bump = state.bump,
         constraint = admin.key() == state.admin @ ControllerError::UnauthorizedAdmin
     )]
@@ -56,7 +56,7 @@ pub struct WithdrawTreasury<'info> {
     /// CHECK: Treasury SOL vault
     #[account(
         mut,
-        seeds = [b"treasury_sol"],
+        seeds = [b"treasury_sol", state.key().as_ref()],
         bump = state.sol_vault_bump
     )]
     pub sol_vault: AccountInfo<'info>,
@@ -3,16 +3,24 @@ use anchor_lang::prelude::*;
 declare_id!("CtrlrXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX");
 
 pub mod error;
+pub mod events;
 pub mod instructions;
+pub mod oracle;
+pub mod pool;
 pub mod state;
 
 use instructions::*;
+use state::{
+    PERMISSION_ALL, PERMISSION_ROUTE_AUTO_LP, PERMISSION_ROUTE_BUYBACK, PERMISSION_ROUTE_BURN,
+    PERMISSION_ROUTE_REWARDS,
+};
 
 #[program]
 pub mod controller {
     use super::*;
 
-    /// Initialize the controller with admin and default config
+    /// Initialize a controller instance for a token mint with admin and default config.
+    /// Must be authorized by the mint authority or the program upgrade authority.
     pub fn initialize(
         ctx: Context<Initialize>,
         bump: u8,
@@ -36,33 +44,152 @@ pub mod controller {
         instructions::config::update_max_trade_handler(ctx, max_trade_lamports)
     }
 
-    /// Add authorized keeper (admin only)
-    pub fn add_keeper(ctx: Context<UpdateConfig>, keeper: Pubkey) -> Result<()> {
-        instructions::config::add_keeper_handler(ctx, keeper)
+    /// Update volume periods and global volume caps (admin only)
+    pub fn update_volume_limits(ctx: Context<UpdateConfig>, limits: VolumeLimits) -> Result<()> {
+        instructions::config::update_volume_limits_handler(ctx, limits)
     }
 
-    /// Remove authorized keeper (admin only)
-    pub fn remove_keeper(ctx: Context<UpdateConfig>, keeper: Pubkey) -> Result<()> {
-        instructions::config::remove_keeper_handler(ctx, keeper)
+    /// Update minimum seconds between swaps (admin only)
+    pub fn update_swap_interval(ctx: Context<UpdateConfig>, min_swap_interval_seconds: i64) -> Result<()> {
+        instructions::config::update_swap_interval_handler(ctx, min_swap_interval_seconds)
+    }
+
+    /// Update the price oracle used to bound swap output (admin only)
+    pub fn update_oracle(ctx: Context<UpdateConfig>, oracle: OracleConfig) -> Result<()> {
+        instructions::config::update_oracle_handler(ctx, oracle)
+    }
+
+    /// Update circuit breaker thresholds (admin only)
+    pub fn update_circuit_breaker(ctx: Context<UpdateConfig>, breaker: CircuitBreakerConfig) -> Result<()> {
+        instructions::config::update_circuit_breaker_handler(ctx, breaker)
+    }
+
+    /// Keeper reports the realized output of a swap; pauses on a slippage spike
+    pub fn report_swap_result(
+        ctx: Context<ReportToBreaker>,
+        expected_out: u64,
+        actual_out: u64,
+    ) -> Result<()> {
+        instructions::breaker::report_swap_result_handler(ctx, expected_out, actual_out)
+    }
+
+    /// Keeper with the report permission reports a failed operation; pauses once failures
+    /// in the window reach the limit
+    pub fn report_failure(ctx: Context<ReportToBreaker>, code: u32) -> Result<()> {
+        instructions::breaker::report_failure_handler(ctx, code)
+    }
+
+    /// Register a constant-product pool for reserve-based price checks (admin only)
+    pub fn add_pool(ctx: Context<AddPool>, params: PoolParams) -> Result<()> {
+        instructions::pool::add_pool_handler(ctx, params)
+    }
+
+    /// Update a pool's layout, fee or reference price (admin only)
+    pub fn update_pool(ctx: Context<UpdatePool>, params: PoolParams) -> Result<()> {
+        instructions::pool::update_pool_handler(ctx, params)
+    }
+
+    /// Remove a registered pool (admin only)
+    pub fn remove_pool(ctx: Context<RemovePool>) -> Result<()> {
+        instructions::pool::remove_pool_handler(ctx)
+    }
+
+    /// Register a keeper account with its permissions and limits (admin only)
+    pub fn add_keeper(ctx: Context<AddKeeper>, keeper: Pubkey, scope: KeeperScope) -> Result<()> {
+        instructions::keeper::add_keeper_handler(ctx, keeper, scope)
+    }
+
+    /// Update a keeper's permissions and limits (admin only)
+    pub fn set_keeper_scope(ctx: Context<SetKeeperScope>, scope: KeeperScope) -> Result<()> {
+        instructions::keeper::set_keeper_scope_handler(ctx, scope)
+    }
+
+    /// Close a keeper account (admin only)
+    pub fn remove_keeper(ctx: Context<RemoveKeeper>) -> Result<()> {
+        instructions::keeper::remove_keeper_handler(ctx)
+    }
+
+    /// Suspend or reactivate a keeper (admin only)
+    pub fn set_keeper_active(ctx: Context<SetKeeperActive>, active: bool) -> Result<()> {
+        instructions::keeper::set_keeper_active_handler(ctx, active)
     }
 
     /// Add allowed DEX program (admin only)
-    pub fn add_dex(ctx: Context<UpdateConfig>, dex_program: Pubkey) -> Result<()> {
-        instructions::config::add_dex_handler(ctx, dex_program)
+    pub fn add_dex(ctx: Context<UpdateConfig>, entry: DexEntry) -> Result<()> {
+        instructions::config::add_dex_handler(ctx, entry)
+    }
+
+    /// Remove a DEX program from the allowlist (admin only)
+    pub fn remove_dex(ctx: Context<UpdateConfig>, dex_program: Pubkey) -> Result<()> {
+        instructions::config::remove_dex_handler(ctx, dex_program)
+    }
+
+    /// Replace or update an allowlist entry in place (admin only)
+    pub fn replace_dex(ctx: Context<UpdateConfig>, dex_program: Pubkey, entry: DexEntry) -> Result<()> {
+        instructions::config::replace_dex_handler(ctx, dex_program, entry)
+    }
+
+    /// Require swaps to use the guarded begin/end swap pair (admin only)
+    pub fn set_swap_guard(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
+        instructions::config::set_swap_guard_handler(ctx, enabled)
+    }
+
+    /// Approve a program a guarded swap transaction may also invoke (admin only)
+    pub fn add_approved_program(ctx: Context<UpdateConfig>, program_id: Pubkey) -> Result<()> {
+        instructions::config::add_approved_program_handler(ctx, program_id)
+    }
+
+    /// Remove a program from the guarded swap approved programs (admin only)
+    pub fn remove_approved_program(ctx: Context<UpdateConfig>, program_id: Pubkey) -> Result<()> {
+        instructions::config::remove_approved_program_handler(ctx, program_id)
     }
 
-    /// Pause/unpause the flywheel (admin only)
+    /// Pause/unpause the flywheel; unpausing clears a circuit breaker trip (admin only)
     pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
         instructions::config::set_paused_handler(ctx, paused)
     }
 
-    /// Keeper executes a swap (with on-chain validation)
+    /// Keeper executes a swap (with on-chain validation), as a chunk of the active execution plan if any.
+    /// Either direction between SOL and a treasury token; limits count the SOL side, quoted for token sales.
+    /// Output is bounded by the configured oracle, a registered pool's reserves, or both.
     pub fn execute_swap(
         ctx: Context<ExecuteSwap>,
+        direction: SwapDirection,
         amount_in: u64,
         min_amount_out: u64,
     ) -> Result<()> {
-        instructions::swap::handler(ctx, amount_in, min_amount_out)
+        instructions::swap::handler(ctx, direction, amount_in, min_amount_out)
+    }
+
+    /// Keeper opens a guarded swap, as a chunk of the active execution plan if any; the transaction
+    /// must be begin_swap, the DEX swap, then end_swap, with other instructions limited to
+    /// system, token, associated token, compute budget and admin-approved programs
+    pub fn begin_swap(
+        ctx: Context<BeginSwap>,
+        direction: SwapDirection,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        instructions::guard::begin_swap_handler(ctx, direction, amount_in, min_amount_out)
+    }
+
+    /// Keeper settles a guarded swap against the treasury token or wSOL balance, unwrapping wSOL
+    pub fn end_swap(ctx: Context<EndSwap>) -> Result<()> {
+        instructions::guard::end_swap_handler(ctx)
+    }
+
+    /// Create a chunked execution plan that every swap fills until it is closed (admin only)
+    pub fn create_execution_plan(
+        ctx: Context<CreateExecutionPlan>,
+        plan_id: u64,
+        params: ExecutionPlanParams,
+    ) -> Result<()> {
+        instructions::plan::create_handler(ctx, plan_id, params)
+    }
+
+    /// Close an execution plan, completed or not, releasing swaps from it (admin only)
+    pub fn close_execution_plan(ctx: Context<CloseExecutionPlan>) -> Result<()> {
+        instructions::plan::close_handler(ctx)
     }
 
     /// Keeper routes SOL to different destinations
@@ -111,7 +238,172 @@ pub struct ControllerConfig {
     pub weights: RoutingWeights,
     pub max_slippage_bps: u16,
     pub max_trade_lamports: u64,
-    pub max_trades_per_day: u16,
+    pub max_trades_per_period: u16,
+    pub volume_limits: VolumeLimits,
+    pub min_swap_interval_seconds: i64,
+}
+
+/// Price oracle used to bound swap output
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct OracleConfig {
+    /// Pyth-style price account quoting output token units per lamport
+    pub price_account: Pubkey,
+    /// Maximum age of the aggregate price
+    pub max_staleness_seconds: i64,
+    /// Maximum confidence interval relative to price, in basis points
+    pub max_confidence_bps: u16,
+}
+
+impl OracleConfig {
+    pub const LEN: usize = 32 + 8 + 2;
+
+    pub fn is_configured(&self) -> bool {
+        self.price_account != Pubkey::default()
+    }
+
+    pub fn validate(&self) -> bool {
+        self.max_staleness_seconds > 0 && self.max_confidence_bps <= 10_000
+    }
+}
+
+/// Circuit breaker thresholds; a zero disables that check
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct CircuitBreakerConfig {
+    /// Length of the outflow and failure window
+    pub window_seconds: i64,
+    /// Maximum treasury outflow per window, in basis points of the SOL balance at window start;
+    /// token sales count at their quoted lamport value
+    pub max_outflow_bps: u16,
+    /// Maximum realized slippage below the quote, measured by `end_swap` or reported by a keeper, in basis points
+    pub max_realized_slippage_bps: u16,
+    /// Reported failures per window that trip the breaker
+    pub max_failures: u16,
+}
+
+impl CircuitBreakerConfig {
+    pub const LEN: usize = 8 + 2 + 2 + 2;
+
+    pub fn validate(&self) -> bool {
+        self.window_seconds > 0
+            && self.max_outflow_bps <= 10_000
+            && self.max_realized_slippage_bps <= 10_000
+    }
+}
+
+/// Layout and price bounds of a constant-product pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct PoolParams {
+    /// Allowlisted DEX program that owns the pool
+    pub dex_program: Pubkey,
+    /// Mint of the pool's token side, paired with SOL
+    pub token_mint: Pubkey,
+    /// Byte offset of the SOL reserve (u64) in the pool account
+    pub sol_reserve_offset: u32,
+    /// Byte offset of the token reserve (u64) in the pool account
+    pub token_reserve_offset: u32,
+    /// Pool swap fee in basis points
+    pub fee_bps: u16,
+    /// Reference spot price, output units per lamport scaled by 1e9 (0 = no check)
+    pub reference_price: u64,
+    /// Maximum deviation of the pool spot price from the reference, in basis points
+    pub max_deviation_bps: u16,
+}
+
+impl PoolParams {
+    pub fn validate(&self) -> bool {
+        self.fee_bps < 10_000 && self.max_deviation_bps <= 10_000
+    }
+}
+
+/// Parameters of a chunked execution plan
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct ExecutionPlanParams {
+    /// Total lamports to swap
+    pub total_amount: u64,
+    /// Maximum lamports per chunk
+    pub chunk_size: u64,
+    /// Minimum seconds between chunks
+    pub min_interval_seconds: i64,
+    /// Unix timestamp after which the plan can no longer be filled
+    pub expiry: i64,
+}
+
+impl ExecutionPlanParams {
+    pub fn validate(&self) -> bool {
+        self.total_amount > 0
+            && self.chunk_size > 0
+            && self.chunk_size <= self.total_amount
+            && self.min_interval_seconds >= 0
+    }
+}
+
+/// Swap volume accounting periods and global caps
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct VolumeLimits {
+    /// Length of the fixed period trade counts and period volume reset on (e.g. 86400)
+    pub period_seconds: i64,
+    /// Lamports swappable per period (0 = no cap)
+    pub max_period_lamports: u64,
+    /// Length of the rolling volume window (e.g. 4 hours)
+    pub window_seconds: i64,
+    /// Lamports swappable within any rolling window (0 = no cap); enforced against the
+    /// current and whole previous window bucket, so it never admits more than the cap
+    pub max_window_lamports: u64,
+}
+
+impl VolumeLimits {
+    pub const LEN: usize = 8 + 8 + 8 + 8;
+
+    pub fn validate(&self) -> bool {
+        self.period_seconds > 0 && self.window_seconds > 0
+    }
+}
+
+/// DEX allowlist entry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct DexEntry {
+    /// DEX program ID
+    pub program_id: Pubkey,
+    /// Human-readable label, zero padded
+    pub label: [u8; 32],
+    /// Per-trade lamport cap for this DEX (0 = global limit only)
+    pub max_trade_lamports: u64,
+    /// Disabled entries stay listed but cannot be swapped through
+    pub enabled: bool,
+}
+
+impl DexEntry {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+}
+
+/// Keeper permissions and limits
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct KeeperScope {
+    /// `PERMISSION_*` bits
+    pub permissions: u16,
+    /// Per-swap lamport cap (0 = global limit only)
+    pub max_swap_lamports: u64,
+    /// Per-route lamport cap (0 = no cap)
+    pub max_route_lamports: u64,
+    /// Swap volume cap per period (0 = no cap)
+    pub max_period_lamports: u64,
+    /// Swap volume cap per rolling window (0 = no cap)
+    pub max_window_lamports: u64,
+}
+
+impl KeeperScope {
+    pub fn validate(&self) -> bool {
+        self.permissions & !PERMISSION_ALL == 0
+    }
+}
+
+/// Which side of a swap the treasury sells; limits always count the SOL side
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SwapDirection {
+    /// Treasury SOL in, tokens out; `amount_in` is in lamports
+    SolToToken,
+    /// Treasury tokens in, SOL out; `amount_in` is in token base units
+    TokenToSol,
 }
 
 /// Route action enum
@@ -123,4 +415,16 @@ pub enum RouteAction {
     AutoLp,
 }
 
+impl RouteAction {
+    /// Keeper permission required to route to this destination
+    pub fn permission(&self) -> u16 {
+        match self {
+            RouteAction::Rewards => PERMISSION_ROUTE_REWARDS,
+            RouteAction::Buyback => PERMISSION_ROUTE_BUYBACK,
+            RouteAction::Burn => PERMISSION_ROUTE_BURN,
+            RouteAction::AutoLp => PERMISSION_ROUTE_AUTO_LP,
+        }
+    }
+}
+
 
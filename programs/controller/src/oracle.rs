@@ -0,0 +1,260 @@
+use anchor_lang::prelude::*;
+use crate::error::ControllerError;
+use crate::OracleConfig;
+
+/// Pyth v2 price account magic number
+const PYTH_MAGIC: u32 = 0xa1b2_c3d4;
+/// Pyth account type of price accounts
+const PYTH_PRICE_ACCOUNT: u32 = 3;
+/// Pyth aggregate status meaning the price is currently trading
+const PYTH_STATUS_TRADING: u32 = 1;
+
+// Offsets into a Pyth v2 price account
+const MAGIC_OFFSET: usize = 0;
+const ACCOUNT_TYPE_OFFSET: usize = 8;
+const EXPO_OFFSET: usize = 20;
+const TIMESTAMP_OFFSET: usize = 96;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+const PYTH_PRICE_LEN: usize = 240;
+
+/// Largest price exponent magnitude accepted from an oracle
+const MAX_ORACLE_EXPO: u32 = 18;
+
+/// Aggregate oracle price: output token base units per input lamport, scaled by 10^expo
+#[derive(Clone, Copy, Debug)]
+pub struct OraclePrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+impl OraclePrice {
+    /// Reject non-positive, badly scaled, stale or low-confidence prices
+    pub fn validate(&self, now: i64, config: &OracleConfig) -> Result<()> {
+        require!(self.price > 0, ControllerError::InvalidOraclePrice);
+        require!(
+            self.expo.unsigned_abs() <= MAX_ORACLE_EXPO,
+            ControllerError::InvalidOraclePrice
+        );
+        require!(
+            now.saturating_sub(self.publish_time) <= config.max_staleness_seconds,
+            ControllerError::StaleOraclePrice
+        );
+        let conf_bps = (self.conf as u128) * 10_000 / (self.price as u128);
+        require!(
+            conf_bps <= config.max_confidence_bps as u128,
+            ControllerError::OracleConfidenceTooWide
+        );
+        Ok(())
+    }
+
+    /// Output expected for `amount_in` at this price
+    pub fn quote(&self, amount_in: u64) -> Option<u128> {
+        let scale = 10u128.checked_pow(self.expo.unsigned_abs())?;
+        let gross = (amount_in as u128).checked_mul(self.price as u128)?;
+        if self.expo < 0 {
+            Some(gross / scale)
+        } else {
+            gross.checked_mul(scale)
+        }
+    }
+
+    /// Lamports expected for `amount_in` output token units at this price
+    pub fn quote_inverse(&self, amount_in: u64) -> Option<u128> {
+        let scale = 10u128.checked_pow(self.expo.unsigned_abs())?;
+        if self.expo < 0 {
+            Some((amount_in as u128).checked_mul(scale)? / (self.price as u128))
+        } else {
+            Some((amount_in as u128) / (self.price as u128).checked_mul(scale)?)
+        }
+    }
+}
+
+/// Lowest acceptable output for `expected_out` under `max_slippage_bps`
+pub fn min_out_with_slippage(expected_out: u128, max_slippage_bps: u16) -> Option<u128> {
+    Some(expected_out.checked_mul(10_000u128.saturating_sub(max_slippage_bps as u128))? / 10_000)
+}
+
+/// Read the aggregate price from an oracle account
+pub fn load_price(account: &AccountInfo) -> Result<OraclePrice> {
+    #[cfg(feature = "mock-oracle")]
+    if account.owner == &crate::ID {
+        let data = account.try_borrow_data()?;
+        let feed = crate::state::MockPriceFeed::try_deserialize(&mut &data[..])?;
+        return Ok(OraclePrice {
+            price: feed.price,
+            conf: feed.conf,
+            expo: feed.expo,
+            publish_time: feed.publish_time,
+        });
+    }
+
+    load_pyth_price(&account.try_borrow_data()?)
+}
+
+fn load_pyth_price(data: &[u8]) -> Result<OraclePrice> {
+    require!(data.len() >= PYTH_PRICE_LEN, ControllerError::InvalidOracleAccount);
+    require!(
+        read_u32(data, MAGIC_OFFSET) == PYTH_MAGIC
+            && read_u32(data, ACCOUNT_TYPE_OFFSET) == PYTH_PRICE_ACCOUNT,
+        ControllerError::InvalidOracleAccount
+    );
+    require!(
+        read_u32(data, AGG_STATUS_OFFSET) == PYTH_STATUS_TRADING,
+        ControllerError::InvalidOraclePrice
+    );
+
+    Ok(OraclePrice {
+        price: read_u64(data, AGG_PRICE_OFFSET) as i64,
+        conf: read_u64(data, AGG_CONF_OFFSET),
+        expo: read_u32(data, EXPO_OFFSET) as i32,
+        publish_time: read_u64(data, TIMESTAMP_OFFSET) as i64,
+    })
+}
+
+fn read_u32(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(data[at..at + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(data[at..at + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn config() -> OracleConfig {
+        OracleConfig {
+            price_account: Pubkey::new_unique(),
+            max_staleness_seconds: 60,
+            max_confidence_bps: 100,
+        }
+    }
+
+    /// 2.5 tokens per lamport, published 10 seconds ago with 0.4% confidence
+    fn price() -> OraclePrice {
+        OraclePrice {
+            price: 25_000,
+            conf: 100,
+            expo: -4,
+            publish_time: NOW - 10,
+        }
+    }
+
+    fn pyth_account(price: &OraclePrice, status: u32) -> Vec<u8> {
+        let mut data = vec![0u8; PYTH_PRICE_LEN];
+        data[MAGIC_OFFSET..MAGIC_OFFSET + 4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+        data[ACCOUNT_TYPE_OFFSET..ACCOUNT_TYPE_OFFSET + 4].copy_from_slice(&PYTH_PRICE_ACCOUNT.to_le_bytes());
+        data[EXPO_OFFSET..EXPO_OFFSET + 4].copy_from_slice(&price.expo.to_le_bytes());
+        data[TIMESTAMP_OFFSET..TIMESTAMP_OFFSET + 8].copy_from_slice(&price.publish_time.to_le_bytes());
+        data[AGG_PRICE_OFFSET..AGG_PRICE_OFFSET + 8].copy_from_slice(&price.price.to_le_bytes());
+        data[AGG_CONF_OFFSET..AGG_CONF_OFFSET + 8].copy_from_slice(&price.conf.to_le_bytes());
+        data[AGG_STATUS_OFFSET..AGG_STATUS_OFFSET + 4].copy_from_slice(&status.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn fresh_price_passes() {
+        assert!(price().validate(NOW, &config()).is_ok());
+    }
+
+    #[test]
+    fn stale_price_rejected() {
+        let stale = OraclePrice { publish_time: NOW - 61, ..price() };
+        assert_eq!(
+            stale.validate(NOW, &config()).unwrap_err(),
+            ControllerError::StaleOraclePrice.into()
+        );
+    }
+
+    #[test]
+    fn wide_confidence_rejected() {
+        let wide = OraclePrice { conf: 260, ..price() };
+        assert_eq!(
+            wide.validate(NOW, &config()).unwrap_err(),
+            ControllerError::OracleConfidenceTooWide.into()
+        );
+    }
+
+    #[test]
+    fn non_positive_price_rejected() {
+        for bad in [0, -25_000] {
+            let bad = OraclePrice { price: bad, ..price() };
+            assert_eq!(
+                bad.validate(NOW, &config()).unwrap_err(),
+                ControllerError::InvalidOraclePrice.into()
+            );
+        }
+    }
+
+    #[test]
+    fn out_of_range_expo_rejected() {
+        for expo in [-19, 19, i32::MIN] {
+            let bad = OraclePrice { expo, ..price() };
+            assert_eq!(
+                bad.validate(NOW, &config()).unwrap_err(),
+                ControllerError::InvalidOraclePrice.into()
+            );
+        }
+    }
+
+    #[test]
+    fn quotes_scale_by_expo() {
+        assert_eq!(price().quote(1_000), Some(2_500));
+        assert_eq!(price().quote_inverse(2_500), Some(1_000));
+
+        let positive = OraclePrice { price: 3, expo: 2, ..price() };
+        assert_eq!(positive.quote(10), Some(3_000));
+        assert_eq!(positive.quote_inverse(3_000), Some(10));
+    }
+
+    #[test]
+    fn quote_overflow_is_none() {
+        let huge = OraclePrice { price: i64::MAX, expo: 18, ..price() };
+        assert_eq!(huge.quote(u64::MAX), None);
+    }
+
+    #[test]
+    fn slippage_floor() {
+        assert_eq!(min_out_with_slippage(10_000, 50), Some(9_950));
+        assert_eq!(min_out_with_slippage(10_000, 10_000), Some(0));
+        assert_eq!(min_out_with_slippage(u128::MAX, 1), None);
+    }
+
+    #[test]
+    fn pyth_account_parsed() {
+        let parsed = load_pyth_price(&pyth_account(&price(), PYTH_STATUS_TRADING)).unwrap();
+        assert_eq!(
+            (parsed.price, parsed.conf, parsed.expo, parsed.publish_time),
+            (25_000, 100, -4, NOW - 10)
+        );
+    }
+
+    #[test]
+    fn pyth_account_not_trading_rejected() {
+        assert_eq!(
+            load_pyth_price(&pyth_account(&price(), 0)).unwrap_err(),
+            ControllerError::InvalidOraclePrice.into()
+        );
+    }
+
+    #[test]
+    fn malformed_pyth_account_rejected() {
+        let mut data = pyth_account(&price(), PYTH_STATUS_TRADING);
+        data[MAGIC_OFFSET] ^= 1;
+        assert_eq!(
+            load_pyth_price(&data).unwrap_err(),
+            ControllerError::InvalidOracleAccount.into()
+        );
+        assert_eq!(
+            load_pyth_price(&data[..PYTH_PRICE_LEN - 1]).unwrap_err(),
+            ControllerError::InvalidOracleAccount.into()
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use anchor_lang::prelude::*;
+use crate::error::ControllerError;
+use crate::state::PoolConfig;
+
+/// Fixed-point scale of pool reference prices (output units per input lamport)
+pub const PRICE_SCALE: u128 = 1_000_000_000;
+
+/// Reserves read from a constant-product pool account, oriented input side first
+#[derive(Clone, Copy, Debug)]
+pub struct PoolReserves {
+    /// Input side (SOL as loaded)
+    pub reserve_in: u64,
+    /// Output side (token as loaded)
+    pub reserve_out: u64,
+}
+
+impl PoolReserves {
+    /// Read both reserves at the offsets configured for the pool
+    pub fn load(pool: &AccountInfo, config: &PoolConfig) -> Result<PoolReserves> {
+        let data = pool.try_borrow_data()?;
+        let reserve_in = read_u64(&data, config.sol_reserve_offset as usize)
+            .ok_or(ControllerError::InvalidPoolAccount)?;
+        let reserve_out = read_u64(&data, config.token_reserve_offset as usize)
+            .ok_or(ControllerError::InvalidPoolAccount)?;
+        require!(
+            reserve_in > 0 && reserve_out > 0,
+            ControllerError::InvalidPoolAccount
+        );
+        Ok(PoolReserves { reserve_in, reserve_out })
+    }
+
+    /// Same pool with input and output swapped, for token-to-SOL swaps
+    pub fn reversed(self) -> PoolReserves {
+        PoolReserves {
+            reserve_in: self.reserve_out,
+            reserve_out: self.reserve_in,
+        }
+    }
+
+    /// Constant-product output for `amount_in` after the pool fee
+    pub fn quote(&self, amount_in: u64, fee_bps: u16) -> u128 {
+        let amount_in = (amount_in as u128) * (10_000u128.saturating_sub(fee_bps as u128)) / 10_000;
+        (self.reserve_out as u128) * amount_in / ((self.reserve_in as u128) + amount_in)
+    }
+
+    /// Spot price in output units per input lamport, scaled by `PRICE_SCALE`
+    pub fn spot_price(&self) -> u128 {
+        (self.reserve_out as u128) * PRICE_SCALE / (self.reserve_in as u128)
+    }
+
+    /// Reject a spot price further from the configured reference than its deviation limit
+    pub fn check_deviation(&self, config: &PoolConfig) -> Result<()> {
+        if config.reference_price > 0 {
+            require!(
+                deviation_bps(self.spot_price(), config.reference_price as u128)
+                    <= config.max_deviation_bps as u128,
+                ControllerError::PoolPriceDeviation
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Deviation of `price` from `reference` in basis points
+pub fn deviation_bps(price: u128, reference: u128) -> u128 {
+    price.abs_diff(reference) * 10_000 / reference
+}
+
+fn read_u64(data: &[u8], at: usize) -> Option<u64> {
+    let bytes = data.get(at..at.checked_add(8)?)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> PoolConfig {
+        PoolConfig {
+            pool: Pubkey::new_unique(),
+            dex_program: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            sol_reserve_offset: 8,
+            token_reserve_offset: 16,
+            fee_bps: 30,
+            reference_price: 0,
+            max_deviation_bps: 0,
+            bump: 255,
+        }
+    }
+
+    fn pool_data(sol_reserve: u64, token_reserve: u64) -> Vec<u8> {
+        let mut data = vec![0u8; 24];
+        data[8..16].copy_from_slice(&sol_reserve.to_le_bytes());
+        data[16..24].copy_from_slice(&token_reserve.to_le_bytes());
+        data
+    }
+
+    fn load(data: &mut [u8], config: &PoolConfig) -> Result<PoolReserves> {
+        let (key, owner) = (config.pool, config.dex_program);
+        let mut lamports = 0;
+        let pool = AccountInfo::new(&key, false, false, &mut lamports, data, &owner, false, 0);
+        PoolReserves::load(&pool, config)
+    }
+
+    #[test]
+    fn load_reads_configured_offsets() {
+        let reserves = load(&mut pool_data(1_000, 5_000), &config()).unwrap();
+        assert_eq!((reserves.reserve_in, reserves.reserve_out), (1_000, 5_000));
+    }
+
+    #[test]
+    fn load_rejects_zero_reserves() {
+        for data in [pool_data(0, 5_000), pool_data(1_000, 0)] {
+            let res = load(&mut data.clone(), &config());
+            assert_eq!(res.unwrap_err(), ControllerError::InvalidPoolAccount.into());
+        }
+    }
+
+    #[test]
+    fn load_rejects_offsets_past_the_data() {
+        let mut config = config();
+        config.token_reserve_offset = 17;
+        let res = load(&mut pool_data(1_000, 5_000), &config);
+        assert_eq!(res.unwrap_err(), ControllerError::InvalidPoolAccount.into());
+
+        config.token_reserve_offset = u32::MAX;
+        let res = load(&mut pool_data(1_000, 5_000), &config);
+        assert_eq!(res.unwrap_err(), ControllerError::InvalidPoolAccount.into());
+    }
+
+    #[test]
+    fn quote_applies_fee_and_constant_product() {
+        let reserves = PoolReserves { reserve_in: 1_000_000, reserve_out: 2_000_000 };
+        // 10_000 in, 9_970 after a 0.3% fee: 2_000_000 * 9_970 / 1_009_970
+        assert_eq!(reserves.quote(10_000, 30), 19_743);
+        assert_eq!(reserves.quote(10_000, 0), 19_801);
+        assert_eq!(reserves.quote(0, 30), 0);
+    }
+
+    #[test]
+    fn quote_stays_within_u128_at_max_reserves() {
+        let reserves = PoolReserves { reserve_in: u64::MAX, reserve_out: u64::MAX };
+        let out = reserves.quote(u64::MAX, 0);
+        assert_eq!(out, (u64::MAX / 2) as u128);
+        assert!(reserves.quote(u64::MAX, 9_999) < u64::MAX as u128);
+
+        let thin = PoolReserves { reserve_in: 1, reserve_out: u64::MAX };
+        assert!(thin.quote(u64::MAX, 0) < u64::MAX as u128);
+        assert_eq!(thin.spot_price(), u64::MAX as u128 * PRICE_SCALE);
+    }
+
+    #[test]
+    fn reversed_quotes_token_to_sol() {
+        // 2 tokens per lamport, so a token-to-SOL swap is quoted on the reversed pool
+        let reserves = PoolReserves { reserve_in: 1_000_000, reserve_out: 2_000_000 };
+        let reversed = reserves.reversed();
+        assert_eq!((reversed.reserve_in, reversed.reserve_out), (2_000_000, 1_000_000));
+        // 20_000 tokens in, 19_940 after the fee: 1_000_000 * 19_940 / 2_019_940
+        assert_eq!(reversed.quote(20_000, 30), 9_871);
+        assert_eq!(reversed.spot_price(), PRICE_SCALE / 2);
+    }
+
+    #[test]
+    fn deviation_limit_is_inclusive() {
+        let reference = 2 * PRICE_SCALE;
+        assert_eq!(deviation_bps(reference, reference), 0);
+        // 1% either side of the reference
+        assert_eq!(deviation_bps(reference * 101 / 100, reference), 100);
+        assert_eq!(deviation_bps(reference * 99 / 100, reference), 100);
+        assert_eq!(deviation_bps(reference * 101 / 100 + 1, reference), 100);
+        assert_eq!(deviation_bps(reference * 1_011 / 1_000, reference), 110);
+        // Spot price of a pool at max reserves against the smallest reference
+        assert_eq!(
+            deviation_bps(u64::MAX as u128 * PRICE_SCALE, 1),
+            (u64::MAX as u128 * PRICE_SCALE - 1) * 10_000
+        );
+    }
+
+    #[test]
+    fn check_deviation_enforces_the_configured_limit() {
+        let mut config = config();
+        config.reference_price = 2 * PRICE_SCALE as u64;
+        config.max_deviation_bps = 100;
+
+        // Spot prices of exactly 1% above and below the reference pass
+        let above = PoolReserves { reserve_in: 100, reserve_out: 202 };
+        let below = PoolReserves { reserve_in: 100, reserve_out: 198 };
+        assert!(above.check_deviation(&config).is_ok());
+        assert!(below.check_deviation(&config).is_ok());
+
+        let beyond = PoolReserves { reserve_in: 1_000, reserve_out: 2_021 };
+        assert_eq!(
+            beyond.check_deviation(&config).unwrap_err(),
+            ControllerError::PoolPriceDeviation.into()
+        );
+
+        // A zero reference disables the check
+        config.reference_price = 0;
+        assert!(beyond.check_deviation(&config).is_ok());
+    }
+}
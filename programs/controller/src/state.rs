@@ -1,16 +1,23 @@
 use anchor_lang::prelude::*;
-use crate::{RoutingWeights, ControllerConfig};
+use crate::{RoutingWeights, ControllerConfig, CircuitBreakerConfig, DexEntry, OracleConfig, PoolParams, SwapDirection, VolumeLimits};
+use crate::events::TripReason;
 
-/// Maximum number of authorized keepers
-pub const MAX_KEEPERS: usize = 5;
 /// Maximum number of allowed DEX programs
 pub const MAX_DEX_PROGRAMS: usize = 10;
 
-/// Global controller state
+/// Maximum number of admin-approved programs allowed alongside a guarded swap
+pub const MAX_APPROVED_PROGRAMS: usize = 8;
+
+/// Upper bound of `max_slippage_bps` (100%)
+pub const MAX_SLIPPAGE_BPS: u16 = 10_000;
+
+/// Controller instance state, one per flywheel token mint
 #[account]
 pub struct ControllerState {
     /// Admin authority (should be multisig)
     pub admin: Pubkey,
+    /// Flywheel token mint; namespaces this instance's PDAs
+    pub mint: Pubkey,
     /// Paused flag - stops all keeper operations
     pub paused: bool,
     /// Routing weights configuration
@@ -19,18 +26,18 @@ pub struct ControllerState {
     pub max_slippage_bps: u16,
     /// Maximum trade size in lamports per transaction
     pub max_trade_lamports: u64,
-    /// Maximum trades per day
-    pub max_trades_per_day: u16,
-    /// Trades executed today
-    pub trades_today: u16,
-    /// Day counter (unix timestamp / 86400)
-    pub current_day: u64,
-    /// Authorized keeper addresses
-    pub keepers: [Pubkey; MAX_KEEPERS],
-    /// Number of active keepers
-    pub num_keepers: u8,
-    /// Allowed DEX program IDs for swaps
-    pub allowed_dex_programs: [Pubkey; MAX_DEX_PROGRAMS],
+    /// Maximum trades per volume period
+    pub max_trades_per_period: u16,
+    /// Trades executed in the current volume period
+    pub trades_this_period: u16,
+    /// Period and rolling window lengths and global volume caps
+    pub volume_limits: VolumeLimits,
+    /// Global swap volume
+    pub volume: VolumeTracker,
+    /// Number of registered keeper accounts
+    pub num_keepers: u16,
+    /// Allowed DEX programs for swaps
+    pub allowed_dexes: [DexEntry; MAX_DEX_PROGRAMS],
     /// Number of allowed DEX programs
     pub num_dex_programs: u8,
     /// Treasury SOL vault bump
@@ -49,6 +56,26 @@ pub struct ControllerState {
     pub total_routed_auto_lp: u64,
     /// Total tokens burned
     pub total_tokens_burned: u64,
+    /// Minimum seconds between swaps
+    pub min_swap_interval_seconds: i64,
+    /// Timestamp of the last swap
+    pub last_swap_at: i64,
+    /// Price oracle bounding swap output
+    pub oracle: OracleConfig,
+    /// Circuit breaker thresholds
+    pub breaker: CircuitBreakerConfig,
+    /// Circuit breaker counters for the current window
+    pub breaker_window: BreakerWindow,
+    /// Reason of the last circuit breaker trip, cleared on unpause
+    pub trip_reason: Option<TripReason>,
+    /// Swaps must use the introspected begin/end swap pair instead of `execute_swap`
+    pub swap_guard_enabled: bool,
+    /// Programs a guarded swap transaction may also invoke, besides the built-in set
+    pub approved_programs: [Pubkey; MAX_APPROVED_PROGRAMS],
+    /// Number of approved programs
+    pub num_approved_programs: u8,
+    /// Execution plan every swap must fill while it is open, or default when none
+    pub active_plan: Pubkey,
     /// Reserved for future use
     pub _reserved: [u8; 64],
 }
@@ -56,16 +83,17 @@ pub struct ControllerState {
 impl ControllerState {
     pub const LEN: usize = 8 + // discriminator
         32 + // admin
+        32 + // mint
         1 +  // paused
         4 +  // weights (4 bytes)
         2 +  // max_slippage_bps
         8 +  // max_trade_lamports
-        2 +  // max_trades_per_day
-        2 +  // trades_today
-        8 +  // current_day
-        (32 * MAX_KEEPERS) + // keepers
-        1 +  // num_keepers
-        (32 * MAX_DEX_PROGRAMS) + // allowed_dex_programs
+        2 +  // max_trades_per_period
+        2 +  // trades_this_period
+        VolumeLimits::LEN + // volume_limits
+        VolumeTracker::LEN + // volume
+        2 +  // num_keepers
+        (DexEntry::LEN * MAX_DEX_PROGRAMS) + // allowed_dexes
         1 +  // num_dex_programs
         1 +  // sol_vault_bump
         1 +  // token_vault_bump
@@ -75,24 +103,209 @@ impl ControllerState {
         8 +  // total_routed_burn
         8 +  // total_routed_auto_lp
         8 +  // total_tokens_burned
+        8 +  // min_swap_interval_seconds
+        8 +  // last_swap_at
+        OracleConfig::LEN + // oracle
+        CircuitBreakerConfig::LEN + // breaker
+        BreakerWindow::LEN + // breaker_window
+        2 +  // trip_reason
+        1 +  // swap_guard_enabled
+        (32 * MAX_APPROVED_PROGRAMS) + // approved_programs
+        1 +  // num_approved_programs
+        32 + // active_plan
         64;  // reserved
 
-    pub fn is_keeper(&self, key: &Pubkey) -> bool {
-        for i in 0..self.num_keepers as usize {
-            if self.keepers[i] == *key {
-                return true;
-            }
-        }
-        false
+    /// Index of the allowlist entry for `program_id`
+    pub fn find_dex(&self, program_id: &Pubkey) -> Option<usize> {
+        self.allowed_dexes[..self.num_dex_programs as usize]
+            .iter()
+            .position(|entry| entry.program_id == *program_id)
+    }
+
+    /// Enabled allowlist entry for `program_id`
+    pub fn allowed_dex(&self, program_id: &Pubkey) -> Option<&DexEntry> {
+        self.find_dex(program_id)
+            .map(|idx| &self.allowed_dexes[idx])
+            .filter(|entry| entry.enabled)
+    }
+
+    /// Index of `program_id` in the guarded swap approved programs
+    pub fn find_approved_program(&self, program_id: &Pubkey) -> Option<usize> {
+        self.approved_programs[..self.num_approved_programs as usize]
+            .iter()
+            .position(|approved| approved == program_id)
     }
+}
+
+/// Lamport volume over the current fixed period and a two-bucket rolling window
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct VolumeTracker {
+    /// Current period index (unix timestamp / period_seconds)
+    pub period: u64,
+    /// Lamports swapped in the current period
+    pub period_volume: u64,
+    /// Start of the current window bucket
+    pub bucket_start: i64,
+    /// Lamports swapped in the current window bucket
+    pub bucket_volume: u64,
+    /// Lamports swapped in the previous window bucket
+    pub previous_bucket_volume: u64,
+}
+
+impl VolumeTracker {
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 8;
+
+    /// Advance buckets to `now`; returns true when a new period started
+    pub fn roll(&mut self, now: i64, limits: &VolumeLimits) -> bool {
+        let bucket_start = now - now.rem_euclid(limits.window_seconds);
+        if bucket_start != self.bucket_start {
+            self.previous_bucket_volume = if bucket_start == self.bucket_start + limits.window_seconds {
+                self.bucket_volume
+            } else {
+                0
+            };
+            self.bucket_volume = 0;
+            self.bucket_start = bucket_start;
+        }
 
-    pub fn is_allowed_dex(&self, program_id: &Pubkey) -> bool {
-        for i in 0..self.num_dex_programs as usize {
-            if self.allowed_dex_programs[i] == *program_id {
-                return true;
-            }
+        let period = (now / limits.period_seconds) as u64;
+        if period == self.period {
+            return false;
         }
-        false
+        self.period = period;
+        self.period_volume = 0;
+        true
+    }
+
+    /// Upper bound on volume over the last `window_seconds`: the current bucket plus the
+    /// whole previous bucket, which together cover any window ending now
+    pub fn window_volume(&self) -> u64 {
+        self.bucket_volume.saturating_add(self.previous_bucket_volume)
+    }
+
+    /// Whether `amount` more keeps period and window volume within their caps (0 = no cap)
+    pub fn allows(&self, amount: u64, max_period: u64, max_window: u64) -> bool {
+        let period_ok = max_period == 0 || self.period_volume.saturating_add(amount) <= max_period;
+        let window_ok = max_window == 0
+            || self.window_volume().saturating_add(amount) <= max_window;
+        period_ok && window_ok
+    }
+
+    pub fn record(&mut self, amount: u64) -> Option<()> {
+        self.period_volume = self.period_volume.checked_add(amount)?;
+        self.bucket_volume = self.bucket_volume.checked_add(amount)?;
+        Some(())
+    }
+}
+
+/// Treasury outflow and keeper failures in the current circuit breaker window
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct BreakerWindow {
+    /// Window start timestamp
+    pub start: i64,
+    /// Treasury balance when the window started
+    pub start_balance: u64,
+    /// Lamports that left the treasury in this window
+    pub outflow: u64,
+    /// Failures reported in this window
+    pub failures: u16,
+}
+
+impl BreakerWindow {
+    pub const LEN: usize = 8 + 8 + 8 + 2;
+}
+
+/// Keeper may call `execute_swap`
+pub const PERMISSION_SWAP: u16 = 1 << 0;
+/// Keeper may route funds to rewards
+pub const PERMISSION_ROUTE_REWARDS: u16 = 1 << 1;
+/// Keeper may route funds to buyback
+pub const PERMISSION_ROUTE_BUYBACK: u16 = 1 << 2;
+/// Keeper may route funds to burn
+pub const PERMISSION_ROUTE_BURN: u16 = 1 << 3;
+/// Keeper may route funds to auto-LP
+pub const PERMISSION_ROUTE_AUTO_LP: u16 = 1 << 4;
+/// Keeper may report swap results and failures to the circuit breaker
+pub const PERMISSION_REPORT: u16 = 1 << 5;
+/// All defined permission bits
+pub const PERMISSION_ALL: u16 = PERMISSION_SWAP
+    | PERMISSION_ROUTE_REWARDS
+    | PERMISSION_ROUTE_BUYBACK
+    | PERMISSION_ROUTE_BURN
+    | PERMISSION_ROUTE_AUTO_LP
+    | PERMISSION_REPORT;
+
+/// Whether a registered keeper may currently act
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeeperStatus {
+    Active,
+    Suspended,
+}
+
+/// Registered keeper, one PDA per keeper key
+#[account]
+pub struct KeeperAccount {
+    /// Keeper signing key
+    pub keeper: Pubkey,
+    /// Active or suspended by the admin
+    pub status: KeeperStatus,
+    /// `PERMISSION_*` bits granted to this keeper
+    pub permissions: u16,
+    /// Per-swap lamport cap for this keeper (0 = global limit only)
+    pub max_swap_lamports: u64,
+    /// Per-route lamport cap for this keeper (0 = no cap)
+    pub max_route_lamports: u64,
+    /// Swap volume cap per period for this keeper (0 = no cap)
+    pub max_period_lamports: u64,
+    /// Swap volume cap per rolling window for this keeper (0 = no cap)
+    pub max_window_lamports: u64,
+    /// Swap volume of this keeper
+    pub volume: VolumeTracker,
+    /// Swaps executed
+    pub swaps_executed: u64,
+    /// Routing calls executed
+    pub routes_executed: u64,
+    /// Total lamports swapped
+    pub lamports_swapped: u64,
+    /// Total lamports routed
+    pub lamports_routed: u64,
+    /// Registration timestamp
+    pub registered_at: i64,
+    /// Last swap or route timestamp
+    pub last_active_at: i64,
+    /// Bump
+    pub bump: u8,
+}
+
+impl KeeperAccount {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // keeper
+        1 +  // status
+        2 +  // permissions
+        8 +  // max_swap_lamports
+        8 +  // max_route_lamports
+        8 +  // max_period_lamports
+        8 +  // max_window_lamports
+        VolumeTracker::LEN + // volume
+        8 +  // swaps_executed
+        8 +  // routes_executed
+        8 +  // lamports_swapped
+        8 +  // lamports_routed
+        8 +  // registered_at
+        8 +  // last_active_at
+        1;   // bump
+
+    pub fn is_active(&self) -> bool {
+        self.status == KeeperStatus::Active
+    }
+
+    pub fn has_permission(&self, permission: u16) -> bool {
+        self.permissions & permission == permission
+    }
+
+    /// Whether `amount` is within `cap`, where a zero cap means unlimited
+    pub fn within_cap(cap: u64, amount: u64) -> bool {
+        cap == 0 || amount <= cap
     }
 }
 
@@ -138,4 +351,231 @@ impl RouteRecord {
     pub const LEN: usize = 8 + 1 + 8 + 32 + 8 + 1;
 }
 
+/// Chunked execution plan for a large swap, filled by keepers over time
+#[account]
+pub struct ExecutionPlan {
+    /// Plan identifier, unique per controller
+    pub plan_id: u64,
+    /// Total lamports to swap
+    pub total_amount: u64,
+    /// Maximum lamports per swap
+    pub chunk_size: u64,
+    /// Minimum seconds between chunks
+    pub min_interval_seconds: i64,
+    /// Unix timestamp after which no more chunks may be filled
+    pub expiry: i64,
+    /// Lamports swapped so far
+    pub filled_amount: u64,
+    /// Chunks executed so far
+    pub chunks_executed: u32,
+    /// Timestamp of the last chunk
+    pub last_chunk_at: i64,
+    /// Bump
+    pub bump: u8,
+}
+
+impl ExecutionPlan {
+    pub const LEN: usize = 8 + // discriminator
+        8 +  // plan_id
+        8 +  // total_amount
+        8 +  // chunk_size
+        8 +  // min_interval_seconds
+        8 +  // expiry
+        8 +  // filled_amount
+        4 +  // chunks_executed
+        8 +  // last_chunk_at
+        1;   // bump
+
+    pub fn remaining(&self) -> u64 {
+        self.total_amount.saturating_sub(self.filled_amount)
+    }
+}
+
+/// Swap in flight between `begin_swap` and `end_swap`, one per controller
+#[account]
+pub struct SwapSession {
+    /// Keeper that received the input
+    pub keeper: Pubkey,
+    /// DEX program invoked between begin and end
+    pub dex_program: Pubkey,
+    /// Treasury token account the output lands in: the bought token, or wSOL for token-to-SOL
+    pub output_account: Pubkey,
+    /// Swap direction
+    pub direction: SwapDirection,
+    /// Input released to the keeper
+    pub amount_in: u64,
+    /// SOL side of the swap counted against limits at begin (quoted for token-to-SOL)
+    pub lamports: u64,
+    /// Minimum output the output account must gain
+    pub min_amount_out: u64,
+    /// Output account balance before the swap
+    pub balance_before: u64,
+    /// Bump
+    pub bump: u8,
+    /// Output quoted by the oracle or pool at begin, realized slippage is measured against it
+    pub expected_out: u64,
+}
+
+impl SwapSession {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // keeper
+        32 + // dex_program
+        32 + // output_account
+        1 +  // direction
+        8 +  // amount_in
+        8 +  // lamports
+        8 +  // min_amount_out
+        8 +  // balance_before
+        1 +  // bump
+        8;   // expected_out
+}
+
+/// Constant-product pool whose reserves bound swap output
+#[account]
+pub struct PoolConfig {
+    /// Pool account reserves are read from
+    pub pool: Pubkey,
+    /// Allowlisted DEX program that owns the pool
+    pub dex_program: Pubkey,
+    /// Mint of the pool's token side
+    pub token_mint: Pubkey,
+    /// Byte offset of the SOL reserve (u64) in the pool account
+    pub sol_reserve_offset: u32,
+    /// Byte offset of the token reserve (u64) in the pool account
+    pub token_reserve_offset: u32,
+    /// Pool swap fee in basis points
+    pub fee_bps: u16,
+    /// Reference spot price, output units per lamport scaled by `PRICE_SCALE` (0 = no check)
+    pub reference_price: u64,
+    /// Maximum deviation of the pool spot price from the reference, in basis points
+    pub max_deviation_bps: u16,
+    /// Bump
+    pub bump: u8,
+}
+
+impl PoolConfig {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // pool
+        32 + // dex_program
+        32 + // token_mint
+        4 +  // sol_reserve_offset
+        4 +  // token_reserve_offset
+        2 +  // fee_bps
+        8 +  // reference_price
+        2 +  // max_deviation_bps
+        1;   // bump
+
+    pub fn apply(&mut self, params: &PoolParams) {
+        self.dex_program = params.dex_program;
+        self.token_mint = params.token_mint;
+        self.sol_reserve_offset = params.sol_reserve_offset;
+        self.token_reserve_offset = params.token_reserve_offset;
+        self.fee_bps = params.fee_bps;
+        self.reference_price = params.reference_price;
+        self.max_deviation_bps = params.max_deviation_bps;
+    }
+}
+
+/// Price feed standing in for a Pyth account in tests. Tests write it directly
+/// with this program as owner; only read when built with `mock-oracle`.
+#[cfg(feature = "mock-oracle")]
+#[account]
+pub struct MockPriceFeed {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One-day periods with one-hour window buckets
+    fn limits() -> VolumeLimits {
+        VolumeLimits {
+            period_seconds: 86_400,
+            max_period_lamports: 0,
+            window_seconds: 3_600,
+            max_window_lamports: 0,
+        }
+    }
+
+    fn tracker_at(now: i64) -> VolumeTracker {
+        let mut tracker = VolumeTracker::default();
+        tracker.roll(now, &limits());
+        tracker
+    }
 
+    #[test]
+    fn roll_moves_bucket_into_previous() {
+        let mut tracker = tracker_at(7_200);
+        tracker.record(500).unwrap();
+
+        // Same bucket: nothing moves
+        assert!(!tracker.roll(10_799, &limits()));
+        assert_eq!((tracker.bucket_volume, tracker.previous_bucket_volume), (500, 0));
+
+        // Next bucket: the current one becomes the previous
+        assert!(!tracker.roll(10_800, &limits()));
+        assert_eq!(tracker.bucket_start, 10_800);
+        assert_eq!((tracker.bucket_volume, tracker.previous_bucket_volume), (0, 500));
+        assert_eq!(tracker.window_volume(), 500);
+    }
+
+    #[test]
+    fn roll_drops_stale_previous_bucket() {
+        let mut tracker = tracker_at(7_200);
+        tracker.record(500).unwrap();
+
+        // Skipping a whole bucket leaves nothing inside the window
+        tracker.roll(14_400, &limits());
+        assert_eq!((tracker.bucket_volume, tracker.previous_bucket_volume), (0, 0));
+        assert_eq!(tracker.window_volume(), 0);
+    }
+
+    #[test]
+    fn roll_resets_period_volume() {
+        let mut tracker = tracker_at(7_200);
+        tracker.record(500).unwrap();
+        assert!(!tracker.roll(86_399, &limits()));
+        assert_eq!(tracker.period_volume, 500);
+
+        assert!(tracker.roll(86_400, &limits()));
+        assert_eq!(tracker.period, 1);
+        assert_eq!(tracker.period_volume, 0);
+    }
+
+    #[test]
+    fn window_cap_counts_whole_previous_bucket() {
+        let mut tracker = tracker_at(7_200);
+        tracker.record(600).unwrap();
+
+        // Late in the next bucket the previous volume still counts in full
+        tracker.roll(14_399, &limits());
+        assert!(tracker.allows(400, 0, 1_000));
+        assert!(!tracker.allows(401, 0, 1_000));
+        tracker.record(400).unwrap();
+
+        // Two consecutive full buckets never exceed the cap
+        tracker.roll(14_400, &limits());
+        assert!(tracker.allows(600, 0, 1_000));
+        assert!(!tracker.allows(601, 0, 1_000));
+    }
+
+    #[test]
+    fn period_cap_and_uncapped_limits() {
+        let mut tracker = tracker_at(7_200);
+        tracker.record(900).unwrap();
+        assert!(tracker.allows(100, 1_000, 0));
+        assert!(!tracker.allows(101, 1_000, 0));
+        assert!(tracker.allows(u64::MAX, 0, 0));
+    }
+
+    #[test]
+    fn record_overflow_is_none() {
+        let mut tracker = tracker_at(7_200);
+        tracker.record(u64::MAX).unwrap();
+        assert!(tracker.record(1).is_none());
+    }
+}
@@ -24,6 +24,82 @@ pub enum DistributorError {
     InvalidSlotRange,
     #[msg("Zero amount")]
     ZeroAmount,
+    #[msg("Epoch is finalized")]
+    EpochFinalized,
+    #[msg("Epoch is not finalized")]
+    EpochNotFinalized,
+    #[msg("Epoch still has open claim receipts")]
+    EpochNotSettled,
+    #[msg("Unsupported leaf version")]
+    UnsupportedLeafVersion,
+    #[msg("Claims exceed epoch totals")]
+    EpochTotalsExceeded,
+    #[msg("Epoch is not claimable yet")]
+    EpochNotClaimable,
+    #[msg("Epoch challenge period has ended")]
+    ChallengePeriodEnded,
+    #[msg("Epoch already vetoed")]
+    EpochVetoed,
+    #[msg("Invalid challenge period")]
+    InvalidChallengePeriod,
+    #[msg("Keeper already attested this epoch")]
+    AlreadyAttested,
+    #[msg("Attestation does not match the pending epoch")]
+    AttestationMismatch,
+    #[msg("Epoch is not awaiting attestations")]
+    NotAttesting,
+    #[msg("Keeper already registered")]
+    KeeperAlreadyExists,
+    #[msg("Keeper not found")]
+    KeeperNotFound,
+    #[msg("Maximum keepers reached")]
+    MaxKeepersReached,
+    #[msg("Invalid publish threshold")]
+    InvalidThreshold,
+    #[msg("Invalid epoch metadata")]
+    InvalidMetadata,
+    #[msg("Epoch does not start right after the last covered slot")]
+    NonContiguousSlotRange,
+    #[msg("Epoch ends in the future")]
+    SlotRangeInFuture,
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+    #[msg("Nothing vested to claim yet")]
+    NothingVested,
+    #[msg("Claim is not eligible for sponsorship")]
+    SponsorshipIneligible,
+    #[msg("Sponsorship cap reached")]
+    SponsorshipCapReached,
+    #[msg("Requested amount exceeds claimable balance")]
+    ClaimExceedsAvailable,
+    #[msg("Reward mint is disabled")]
+    RewardMintDisabled,
+    #[msg("Epoch leaf version does not support reward mint leaves")]
+    MintLeavesUnsupported,
+    #[msg("Voucher claims are disabled")]
+    VouchersDisabled,
+    #[msg("Missing or invalid voucher signature")]
+    InvalidVoucherSignature,
+    #[msg("Voucher expired")]
+    VoucherExpired,
+    #[msg("Voucher epoch budget exceeded")]
+    VoucherBudgetExceeded,
+    #[msg("Receipt close grace period has not elapsed")]
+    ReceiptGracePeriod,
+    #[msg("Epoch has not been vetoed")]
+    EpochNotVetoed,
+    #[msg("Epoch vesting schedule has not completed")]
+    VestingNotComplete,
+    #[msg("Token payout accounts missing or invalid")]
+    MissingTokenAccounts,
+    #[msg("Voucher epoch has not expired")]
+    VoucherEpochActive,
+    #[msg("Voucher epoch still has open nonces")]
+    VoucherNoncesOpen,
+    #[msg("Sponsor usage account required for a sponsored receipt")]
+    SponsorUsageRequired,
+    #[msg("Wallet still has open sponsored receipts")]
+    SponsoredReceiptsOpen,
 }
 
 
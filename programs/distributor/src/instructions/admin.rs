@@ -1,12 +1,12 @@
 use anchor_lang::prelude::*;
-use crate::state::DistributorState;
+use crate::state::{DistributorState, MAX_CHALLENGE_PERIOD, MAX_KEEPERS};
 use crate::error::DistributorError;
 
 #[derive(Accounts)]
 pub struct UpdateAdmin<'info> {
     #[account(
         mut,
-        seeds = [b"distributor_state"],
+        seeds = [b"distributor_state", state.mint.as_ref()],
         bump = state.bump,
         constraint = admin.key() == state.admin @ DistributorError::Unauthorized
     )]
@@ -29,7 +29,7 @@ pub fn update_admin_handler(ctx: Context<UpdateAdmin>, new_admin: Pubkey) -> Res
 pub struct UpdateKeeper<'info> {
     #[account(
         mut,
-        seeds = [b"distributor_state"],
+        seeds = [b"distributor_state", state.mint.as_ref()],
         bump = state.bump,
         constraint = admin.key() == state.admin @ DistributorError::Unauthorized
     )]
@@ -38,12 +38,110 @@ pub struct UpdateKeeper<'info> {
     pub admin: Signer<'info>,
 }
 
-pub fn update_keeper_handler(ctx: Context<UpdateKeeper>, new_keeper: Pubkey) -> Result<()> {
+pub fn add_keeper_handler(ctx: Context<UpdateKeeper>, keeper: Pubkey) -> Result<()> {
     let state = &mut ctx.accounts.state;
-    let old_keeper = state.keeper;
-    state.keeper = new_keeper;
     
-    msg!("Keeper updated from {} to {}", old_keeper, new_keeper);
+    require!(!state.is_keeper(&keeper), DistributorError::KeeperAlreadyExists);
+    require!(
+        (state.num_keepers as usize) < MAX_KEEPERS,
+        DistributorError::MaxKeepersReached
+    );
+    
+    let idx = state.num_keepers as usize;
+    state.keepers[idx] = keeper;
+    state.num_keepers += 1;
+    
+    msg!("Keeper added: {}", keeper);
+    
+    Ok(())
+}
+
+pub fn remove_keeper_handler(ctx: Context<UpdateKeeper>, keeper: Pubkey) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    let num_keepers = state.num_keepers as usize;
+    
+    let idx = state.keepers[..num_keepers]
+        .iter()
+        .position(|k| *k == keeper)
+        .ok_or(DistributorError::KeeperNotFound)?;
+    
+    // The remaining set must still be able to reach the threshold
+    require!(
+        num_keepers > state.publish_threshold as usize,
+        DistributorError::InvalidThreshold
+    );
+    
+    // Shift remaining keepers down
+    state.keepers.copy_within(idx + 1..num_keepers, idx);
+    state.keepers[num_keepers - 1] = Pubkey::default();
+    state.num_keepers -= 1;
+    
+    msg!("Keeper removed: {}", keeper);
+    
+    Ok(())
+}
+
+pub fn set_publish_threshold_handler(ctx: Context<UpdateKeeper>, threshold: u8) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    
+    require!(
+        (1..=state.num_keepers).contains(&threshold),
+        DistributorError::InvalidThreshold
+    );
+    
+    state.publish_threshold = threshold;
+    
+    msg!("Publish threshold set to {} of {} keepers", threshold, state.num_keepers);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn update_guardian_handler(ctx: Context<UpdateGuardian>, new_guardian: Pubkey) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    let old_guardian = state.guardian;
+    state.guardian = new_guardian;
+    
+    msg!("Guardian updated from {} to {}", old_guardian, new_guardian);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetChallengePeriod<'info> {
+    #[account(
+        mut,
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn set_challenge_period_handler(ctx: Context<SetChallengePeriod>, challenge_period: i64) -> Result<()> {
+    require!(
+        (0..=MAX_CHALLENGE_PERIOD).contains(&challenge_period),
+        DistributorError::InvalidChallengePeriod
+    );
+
+    let state = &mut ctx.accounts.state;
+    state.challenge_period = challenge_period;
+    
+    msg!("Challenge period set to {} seconds", challenge_period);
     
     Ok(())
 }
@@ -52,7 +150,7 @@ pub fn update_keeper_handler(ctx: Context<UpdateKeeper>, new_keeper: Pubkey) ->
 pub struct SetPaused<'info> {
     #[account(
         mut,
-        seeds = [b"distributor_state"],
+        seeds = [b"distributor_state", state.mint.as_ref()],
         bump = state.bump,
         constraint = admin.key() == state.admin @ DistributorError::Unauthorized
     )]
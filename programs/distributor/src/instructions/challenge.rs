@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+use crate::state::{DistributorState, EpochState, EpochStatus};
+use crate::error::DistributorError;
+use crate::instructions::publish_epoch::validate_metadata;
+use crate::{EpochMetadata, CURRENT_LEAF_VERSION};
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct VetoEpoch<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = authority.key() == state.guardian || authority.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump,
+        constraint = epoch.status != EpochStatus::Vetoed @ DistributorError::EpochVetoed
+    )]
+    pub epoch: Account<'info, EpochState>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn veto_handler(ctx: Context<VetoEpoch>, epoch_id: u64) -> Result<()> {
+    let epoch = &mut ctx.accounts.epoch;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(
+        epoch.is_challengeable(now),
+        DistributorError::ChallengePeriodEnded
+    );
+
+    epoch.status = EpochStatus::Vetoed;
+
+    msg!(
+        "Epoch {} root vetoed by {}",
+        epoch_id,
+        ctx.accounts.authority.key()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct RepublishEpoch<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = !state.paused @ DistributorError::Paused
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump
+    )]
+    pub epoch: Account<'info, EpochState>,
+
+    #[account(
+        constraint = state.is_keeper(&keeper.key()) @ DistributorError::Unauthorized
+    )]
+    pub keeper: Signer<'info>,
+}
+
+pub fn republish_handler(
+    ctx: Context<RepublishEpoch>,
+    epoch_id: u64,
+    merkle_root: [u8; 32],
+    total_rewards_sol: u64,
+    total_rewards_token: u64,
+    leaf_version: u8,
+    metadata: EpochMetadata,
+) -> Result<()> {
+    let state = &ctx.accounts.state;
+    let epoch = &mut ctx.accounts.epoch;
+    let now = Clock::get()?.unix_timestamp;
+
+    // Only vetoed roots may be replaced, so a single keeper cannot override a quorum
+    require!(
+        epoch.status == EpochStatus::Vetoed,
+        DistributorError::EpochNotVetoed
+    );
+    require!(
+        leaf_version <= CURRENT_LEAF_VERSION,
+        DistributorError::UnsupportedLeafVersion
+    );
+    validate_metadata(&metadata)?;
+
+    // Slot range is kept; the corrected root covers the same epoch and must
+    // collect a fresh quorum of attestations
+    epoch.merkle_root = merkle_root;
+    epoch.total_rewards_sol = total_rewards_sol;
+    epoch.total_rewards_token = total_rewards_token;
+    epoch.leaf_version = leaf_version;
+    epoch.metadata = metadata;
+    epoch.publish_time = now;
+    epoch.reset_attestations(state.publish_threshold);
+    epoch.record_attestation(ctx.accounts.keeper.key(), now, state.challenge_period)?;
+
+    msg!(
+        "Epoch {} republished: {} SOL, {} tokens available (leaf v{}), {}/{} attestations",
+        epoch_id,
+        total_rewards_sol,
+        total_rewards_token,
+        leaf_version,
+        epoch.num_attestations,
+        epoch.required_attestations
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct CloseVetoedEpoch<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        close = publisher,
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump,
+        constraint = epoch.status == EpochStatus::Vetoed @ DistributorError::EpochNotVetoed,
+        constraint = epoch.num_claimants == 0 && epoch.num_reward_mints == 0 @ DistributorError::EpochNotSettled
+    )]
+    pub epoch: Account<'info, EpochState>,
+
+    /// CHECK: Rent destination, must be the keeper that published the epoch
+    #[account(
+        mut,
+        address = epoch.publisher @ DistributorError::Unauthorized
+    )]
+    pub publisher: AccountInfo<'info>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Abandon a vetoed root that will not be republished; vetoed epochs are never
+/// claimable, so there are no receipts to settle first
+pub fn close_vetoed_handler(ctx: Context<CloseVetoedEpoch>, epoch_id: u64) -> Result<()> {
+    msg!(
+        "Vetoed epoch {} closed, rent returned to {}",
+        epoch_id,
+        ctx.accounts.publisher.key()
+    );
+
+    Ok(())
+}
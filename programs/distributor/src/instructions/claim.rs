@@ -1,15 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
 use crate::state::{DistributorState, EpochState, ClaimReceipt};
 use crate::error::DistributorError;
-use crate::{verify_proof, compute_leaf};
+use crate::{verify_proof, verify_sum_proof, compute_leaf, SubtreeSums, LEAF_VERSION_SUM_TREE};
 
 #[derive(Accounts)]
 #[instruction(epoch_id: u64)]
 pub struct Claim<'info> {
     #[account(
         mut,
-        seeds = [b"distributor_state"],
+        seeds = [b"distributor_state", state.mint.as_ref()],
         bump = state.bump,
         constraint = !state.paused @ DistributorError::Paused
     )]
@@ -17,16 +16,17 @@ pub struct Claim<'info> {
 
     #[account(
         mut,
-        seeds = [b"epoch", &epoch_id.to_le_bytes()],
-        bump = epoch.bump
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump,
+        constraint = !epoch.finalized @ DistributorError::EpochFinalized
     )]
     pub epoch: Account<'info, EpochState>,
 
     #[account(
-        init,
+        init_if_needed,
         payer = claimant,
         space = ClaimReceipt::LEN,
-        seeds = [b"claim", &epoch_id.to_le_bytes(), claimant.key().as_ref()],
+        seeds = [b"claim", state.key().as_ref(), &epoch_id.to_le_bytes(), claimant.key().as_ref()],
         bump
     )]
     pub claim_receipt: Account<'info, ClaimReceipt>,
@@ -34,100 +34,310 @@ pub struct Claim<'info> {
     /// CHECK: SOL vault PDA that holds rewards
     #[account(
         mut,
-        seeds = [b"sol_vault"],
+        seeds = [b"sol_vault", state.key().as_ref()],
         bump = state.sol_vault_bump
     )]
     pub sol_vault: AccountInfo<'info>,
 
+    /// CHECK: Payout destination chosen by the leaf owner
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+
     #[account(mut)]
     pub claimant: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn handler(
     ctx: Context<Claim>,
     epoch_id: u64,
     amount_sol: u64,
     amount_token: u64,
     proof: Vec<[u8; 32]>,
+    proof_sums: Vec<SubtreeSums>,
+    withdraw_sol: u64,
+    withdraw_token: u64,
 ) -> Result<()> {
-    let epoch = &mut ctx.accounts.epoch;
-    let claim_receipt = &mut ctx.accounts.claim_receipt;
-    let state = &mut ctx.accounts.state;
+    require!(
+        withdraw_sol > 0 || withdraw_token > 0,
+        DistributorError::ZeroAmount
+    );
+
+    let claimant = ctx.accounts.claimant.key();
+    process_claim(
+        ClaimAccounts {
+            state: &mut ctx.accounts.state,
+            epoch: &mut ctx.accounts.epoch,
+            claim_receipt: &mut ctx.accounts.claim_receipt,
+            receipt_bump: ctx.bumps.claim_receipt,
+            sol_vault: &ctx.accounts.sol_vault,
+            recipient: &ctx.accounts.recipient,
+            wallet: claimant,
+            rent_payer: claimant,
+        },
+        epoch_id,
+        amount_sol,
+        amount_token,
+        proof,
+        proof_sums,
+        Some((withdraw_sol, withdraw_token)),
+    )
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct ClaimFor<'info> {
+    #[account(
+        mut,
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = !state.paused @ DistributorError::Paused
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump,
+        constraint = !epoch.finalized @ DistributorError::EpochFinalized
+    )]
+    pub epoch: Account<'info, EpochState>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = ClaimReceipt::LEN,
+        seeds = [b"claim", state.key().as_ref(), &epoch_id.to_le_bytes(), wallet.key().as_ref()],
+        bump
+    )]
+    pub claim_receipt: Account<'info, ClaimReceipt>,
+
+    /// CHECK: SOL vault PDA that holds rewards
+    #[account(
+        mut,
+        seeds = [b"sol_vault", state.key().as_ref()],
+        bump = state.sol_vault_bump
+    )]
+    pub sol_vault: AccountInfo<'info>,
+
+    /// CHECK: Leaf owner; rewards are always paid to this wallet
+    #[account(mut)]
+    pub wallet: AccountInfo<'info>,
+
+    /// Submits the claim and pays fees and receipt rent
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn claim_for_handler(
+    ctx: Context<ClaimFor>,
+    epoch_id: u64,
+    amount_sol: u64,
+    amount_token: u64,
+    proof: Vec<[u8; 32]>,
+    proof_sums: Vec<SubtreeSums>,
+) -> Result<()> {
+    process_claim(
+        ClaimAccounts {
+            state: &mut ctx.accounts.state,
+            epoch: &mut ctx.accounts.epoch,
+            claim_receipt: &mut ctx.accounts.claim_receipt,
+            receipt_bump: ctx.bumps.claim_receipt,
+            sol_vault: &ctx.accounts.sol_vault,
+            recipient: &ctx.accounts.wallet,
+            wallet: ctx.accounts.wallet.key(),
+            rent_payer: ctx.accounts.relayer.key(),
+        },
+        epoch_id,
+        amount_sol,
+        amount_token,
+        proof,
+        proof_sums,
+        None,
+    )
+}
+
+/// Accounts shared by every claim path
+pub struct ClaimAccounts<'a, 'info> {
+    pub state: &'a mut Account<'info, DistributorState>,
+    pub epoch: &'a mut Account<'info, EpochState>,
+    pub claim_receipt: &'a mut Account<'info, ClaimReceipt>,
+    pub receipt_bump: u8,
+    pub sol_vault: &'a AccountInfo<'info>,
+    /// Receives the released SOL
+    pub recipient: &'a AccountInfo<'info>,
+    /// Leaf owner the allocation belongs to
+    pub wallet: Pubkey,
+    /// Paid the receipt rent; refunded when the receipt is closed
+    pub rent_payer: Pubkey,
+}
+
+/// Verify the leaf for `wallet` and release vested, unclaimed rewards to `recipient`.
+/// `withdraw` requests exact (SOL, token) amounts; `None` releases everything available.
+pub fn process_claim(
+    accounts: ClaimAccounts,
+    epoch_id: u64,
+    amount_sol: u64,
+    amount_token: u64,
+    proof: Vec<[u8; 32]>,
+    proof_sums: Vec<SubtreeSums>,
+    withdraw: Option<(u64, u64)>,
+) -> Result<()> {
+    let ClaimAccounts {
+        state,
+        epoch,
+        claim_receipt,
+        receipt_bump,
+        sol_vault,
+        recipient,
+        wallet,
+        rent_payer,
+    } = accounts;
+    let now = Clock::get()?.unix_timestamp;
+
+    // Root must have survived its challenge period
+    require!(epoch.is_claimable(now), DistributorError::EpochNotClaimable);
 
     // Compute the leaf hash for this claim
     let leaf = compute_leaf(
-        &ctx.accounts.claimant.key(),
+        epoch.leaf_version,
+        &wallet,
         epoch_id,
         amount_sol,
         amount_token,
     );
 
-    // Verify merkle proof
+    // Verify merkle proof; sum tree epochs also prove the root sums equal the epoch totals
+    let valid = if epoch.leaf_version == LEAF_VERSION_SUM_TREE {
+        verify_sum_proof(
+            &proof,
+            &proof_sums,
+            epoch.merkle_root,
+            SubtreeSums {
+                sol: epoch.total_rewards_sol,
+                token: epoch.total_rewards_token,
+            },
+            leaf,
+            SubtreeSums {
+                sol: amount_sol,
+                token: amount_token,
+            },
+        )
+    } else {
+        proof_sums.is_empty() && verify_proof(epoch.leaf_version, &proof, epoch.merkle_root, leaf)
+    };
+    require!(valid, DistributorError::InvalidProof);
+
+    // First claim records the allocation; later claims release the remaining vested balance
+    let is_first_claim = claim_receipt.wallet == Pubkey::default();
+    if is_first_claim {
+        claim_receipt.wallet = wallet;
+        claim_receipt.epoch_id = epoch_id;
+        claim_receipt.amount_sol = amount_sol;
+        claim_receipt.amount_token = amount_token;
+        claim_receipt.bump = receipt_bump;
+        claim_receipt.rent_payer = rent_payer;
+    } else {
+        require!(
+            claim_receipt.amount_sol == amount_sol && claim_receipt.amount_token == amount_token,
+            DistributorError::InvalidProof
+        );
+    }
+    require!(
+        !claim_receipt.is_fully_released(),
+        DistributorError::AlreadyClaimed
+    );
+
+    let available_sol = epoch
+        .vesting
+        .vested_amount(amount_sol, now)
+        .checked_sub(claim_receipt.released_sol)
+        .ok_or(DistributorError::MathOverflow)?;
+    let available_token = epoch
+        .vesting
+        .vested_amount(amount_token, now)
+        .checked_sub(claim_receipt.released_token)
+        .ok_or(DistributorError::MathOverflow)?;
     require!(
-        verify_proof(&proof, epoch.merkle_root, leaf),
-        DistributorError::InvalidProof
+        available_sol > 0 || available_token > 0,
+        DistributorError::NothingVested
+    );
+
+    let (release_sol, release_token) = withdraw.unwrap_or((available_sol, available_token));
+    require!(
+        release_sol <= available_sol && release_token <= available_token,
+        DistributorError::ClaimExceedsAvailable
     );
 
     // Check vault has sufficient balance for SOL
-    let vault_balance = ctx.accounts.sol_vault.lamports();
+    let vault_balance = sol_vault.lamports();
     require!(
-        vault_balance >= amount_sol,
+        vault_balance >= release_sol,
         DistributorError::InsufficientBalance
     );
 
-    // Transfer SOL from vault to claimant
-    if amount_sol > 0 {
-        let seeds = &[b"sol_vault".as_ref(), &[state.sol_vault_bump]];
-        let signer_seeds = &[&seeds[..]];
-
-        // Transfer lamports from vault to claimant
-        **ctx.accounts.sol_vault.try_borrow_mut_lamports()? -= amount_sol;
-        **ctx.accounts.claimant.try_borrow_mut_lamports()? += amount_sol;
+    // Transfer SOL from vault to recipient
+    if release_sol > 0 {
+        **sol_vault.try_borrow_mut_lamports()? -= release_sol;
+        **recipient.try_borrow_mut_lamports()? += release_sol;
     }
 
     // Update epoch claimed totals
     epoch.claimed_sol = epoch
         .claimed_sol
-        .checked_add(amount_sol)
+        .checked_add(release_sol)
         .ok_or(DistributorError::MathOverflow)?;
     epoch.claimed_token = epoch
         .claimed_token
-        .checked_add(amount_token)
-        .ok_or(DistributorError::MathOverflow)?;
-    epoch.num_claimants = epoch
-        .num_claimants
-        .checked_add(1)
+        .checked_add(release_token)
         .ok_or(DistributorError::MathOverflow)?;
+    require!(
+        epoch.claimed_sol <= epoch.total_rewards_sol
+            && epoch.claimed_token <= epoch.total_rewards_token,
+        DistributorError::EpochTotalsExceeded
+    );
+    if is_first_claim {
+        epoch.num_claimants = epoch
+            .num_claimants
+            .checked_add(1)
+            .ok_or(DistributorError::MathOverflow)?;
+    }
 
     // Update global totals
     state.total_sol_distributed = state
         .total_sol_distributed
-        .checked_add(amount_sol)
+        .checked_add(release_sol)
         .ok_or(DistributorError::MathOverflow)?;
     state.total_token_distributed = state
         .total_token_distributed
-        .checked_add(amount_token)
+        .checked_add(release_token)
         .ok_or(DistributorError::MathOverflow)?;
 
-    // Record the claim
-    claim_receipt.wallet = ctx.accounts.claimant.key();
-    claim_receipt.epoch_id = epoch_id;
-    claim_receipt.amount_sol = amount_sol;
-    claim_receipt.amount_token = amount_token;
-    claim_receipt.claimed_at = Clock::get()?.unix_timestamp;
-    claim_receipt.bump = ctx.bumps.claim_receipt;
+    // Record the release
+    claim_receipt.released_sol = claim_receipt
+        .released_sol
+        .checked_add(release_sol)
+        .ok_or(DistributorError::MathOverflow)?;
+    claim_receipt.released_token = claim_receipt
+        .released_token
+        .checked_add(release_token)
+        .ok_or(DistributorError::MathOverflow)?;
+    claim_receipt.claimed_at = now;
 
     msg!(
-        "Claimed {} SOL and {} tokens for epoch {} by {}",
-        amount_sol,
-        amount_token,
+        "Claimed {} SOL and {} tokens for epoch {} by {} to {} ({}/{} SOL released)",
+        release_sol,
+        release_token,
         epoch_id,
-        ctx.accounts.claimant.key()
+        wallet,
+        recipient.key(),
+        claim_receipt.released_sol,
+        claim_receipt.amount_sol
     );
 
     Ok(())
 }
-
-
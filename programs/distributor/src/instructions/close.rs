@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+use crate::state::{DistributorState, EpochState, ClaimReceipt, SponsorUsage, RECEIPT_CLOSE_GRACE_PERIOD};
+use crate::error::DistributorError;
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct FinalizeEpoch<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump,
+        constraint = !epoch.finalized @ DistributorError::EpochFinalized
+    )]
+    pub epoch: Account<'info, EpochState>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn finalize_handler(ctx: Context<FinalizeEpoch>, epoch_id: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let epoch = &mut ctx.accounts.epoch;
+    // Vetoed and still-challengeable roots are not final
+    require!(epoch.is_claimable(now), DistributorError::EpochNotClaimable);
+    // Finalizing mid-vesting would cut off unvested allocations
+    require!(epoch.vesting.is_complete(now), DistributorError::VestingNotComplete);
+
+    epoch.finalized = true;
+    epoch.finalized_at = now;
+
+    msg!(
+        "Epoch {} finalized: {} of {} lamports claimed by {} wallets",
+        epoch_id,
+        epoch.claimed_sol,
+        epoch.total_rewards_sol,
+        epoch.num_claimants
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct CloseClaimReceipt<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump,
+        constraint = epoch.finalized @ DistributorError::EpochNotFinalized
+    )]
+    pub epoch: Account<'info, EpochState>,
+
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [b"claim", state.key().as_ref(), &epoch_id.to_le_bytes(), claim_receipt.wallet.as_ref()],
+        bump = claim_receipt.bump,
+        constraint = authority.key() == claim_receipt.wallet
+            || authority.key() == claim_receipt.rent_payer
+            || authority.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub claim_receipt: Account<'info, ClaimReceipt>,
+
+    /// CHECK: Rent destination, must be the account that paid for the receipt
+    #[account(
+        mut,
+        address = claim_receipt.rent_payer @ DistributorError::Unauthorized
+    )]
+    pub rent_payer: AccountInfo<'info>,
+
+    /// Sponsorship usage of the receipt's wallet; required when the sponsor pool paid the rent
+    #[account(
+        mut,
+        seeds = [b"sponsor_usage", state.key().as_ref(), claim_receipt.wallet.as_ref()],
+        bump = sponsor_usage.bump
+    )]
+    pub sponsor_usage: Option<Account<'info, SponsorUsage>>,
+
+    /// Leaf owner or rent payer, or the admin once the grace period has passed
+    pub authority: Signer<'info>,
+}
+
+pub fn close_receipt_handler(ctx: Context<CloseClaimReceipt>, epoch_id: u64) -> Result<()> {
+    // The receipt can only be closed after finalization, and finalized epochs
+    // reject claims, so re-creating the receipt cannot lead to a second payout.
+    let epoch = &mut ctx.accounts.epoch;
+    let authority = ctx.accounts.authority.key();
+    let receipt = &ctx.accounts.claim_receipt;
+    if authority != receipt.wallet && authority != receipt.rent_payer {
+        // Admin sweeps receipts holders never closed so the epoch can settle
+        require!(
+            Clock::get()?.unix_timestamp >= epoch.finalized_at.saturating_add(RECEIPT_CLOSE_GRACE_PERIOD),
+            DistributorError::ReceiptGracePeriod
+        );
+    }
+
+    epoch.num_receipts_closed = epoch
+        .num_receipts_closed
+        .checked_add(1)
+        .ok_or(DistributorError::MathOverflow)?;
+
+    // Release the wallet's sponsorship usage so it can be closed once all its receipts are
+    let (sponsor_pool, _) = Pubkey::find_program_address(
+        &[b"sponsor_pool", ctx.accounts.state.key().as_ref()],
+        &crate::ID,
+    );
+    if ctx.accounts.claim_receipt.rent_payer == sponsor_pool {
+        let usage = ctx
+            .accounts
+            .sponsor_usage
+            .as_mut()
+            .ok_or(DistributorError::SponsorUsageRequired)?;
+        usage.open_receipts = usage.open_receipts.saturating_sub(1);
+    }
+
+    msg!(
+        "Claim receipt of {} for epoch {} closed, rent returned to {}",
+        ctx.accounts.claim_receipt.wallet,
+        epoch_id,
+        ctx.accounts.rent_payer.key()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct CloseEpoch<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = state.is_keeper(&authority.key()) || authority.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        close = publisher,
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump,
+        constraint = epoch.is_settled() @ DistributorError::EpochNotSettled
+    )]
+    pub epoch: Account<'info, EpochState>,
+
+    /// CHECK: Rent destination, must be the keeper that published the epoch
+    #[account(
+        mut,
+        address = epoch.publisher @ DistributorError::Unauthorized
+    )]
+    pub publisher: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn close_epoch_handler(ctx: Context<CloseEpoch>, epoch_id: u64) -> Result<()> {
+    msg!(
+        "Epoch {} closed, rent returned to {}",
+        epoch_id,
+        ctx.accounts.publisher.key()
+    );
+
+    Ok(())
+}
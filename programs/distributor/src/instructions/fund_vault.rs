@@ -7,7 +7,7 @@ use crate::error::DistributorError;
 #[derive(Accounts)]
 pub struct FundVault<'info> {
     #[account(
-        seeds = [b"distributor_state"],
+        seeds = [b"distributor_state", state.mint.as_ref()],
         bump = state.bump
     )]
     pub state: Account<'info, DistributorState>,
@@ -15,7 +15,7 @@ pub struct FundVault<'info> {
     /// CHECK: SOL vault PDA
     #[account(
         mut,
-        seeds = [b"sol_vault"],
+        seeds = [b"sol_vault", state.key().as_ref()],
         bump = state.sol_vault_bump
     )]
     pub sol_vault: AccountInfo<'info>,
@@ -48,7 +48,7 @@ pub fn handler_sol(ctx: Context<FundVault>, amount: u64) -> Result<()> {
 #[derive(Accounts)]
 pub struct FundTokenVault<'info> {
     #[account(
-        seeds = [b"distributor_state"],
+        seeds = [b"distributor_state", state.mint.as_ref()],
         bump = state.bump
     )]
     pub state: Account<'info, DistributorState>,
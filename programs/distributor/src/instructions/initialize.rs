@@ -1,5 +1,8 @@
 use anchor_lang::prelude::*;
-use crate::state::DistributorState;
+use anchor_lang::solana_program::{bpf_loader_upgradeable, program_option::COption};
+use anchor_spl::token::Mint;
+use crate::state::{DistributorState, MAX_KEEPERS};
+use crate::error::DistributorError;
 
 #[derive(Accounts)]
 #[instruction(bump: u8)]
@@ -8,14 +11,17 @@ pub struct Initialize<'info> {
         init,
         payer = admin,
         space = DistributorState::LEN,
-        seeds = [b"distributor_state"],
+        seeds = [b"distributor_state", mint.key().as_ref()],
         bump
     )]
     pub state: Account<'info, DistributorState>,
 
+    /// Flywheel token mint this instance distributes rewards for
+    pub mint: Account<'info, Mint>,
+
     /// CHECK: SOL vault PDA - just holds lamports
     #[account(
-        seeds = [b"sol_vault"],
+        seeds = [b"sol_vault", state.key().as_ref()],
         bump
     )]
     pub sol_vault: AccountInfo<'info>,
@@ -23,24 +29,66 @@ pub struct Initialize<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
 
+    /// Mint authority of `mint`, or the program upgrade authority for mints whose authority is revoked
+    pub authority: Signer<'info>,
+
+    /// This program's program data; required when `authority` is not the mint authority
+    pub program_data: Option<Account<'info, ProgramData>>,
+
     pub system_program: Program<'info, System>,
 }
 
 pub fn handler(ctx: Context<Initialize>, bump: u8) -> Result<()> {
+    authorize_creator(
+        &ctx.accounts.mint,
+        &ctx.accounts.authority.key(),
+        ctx.accounts.program_data.as_ref(),
+    )?;
+
     let state = &mut ctx.accounts.state;
     
     state.admin = ctx.accounts.admin.key();
-    state.keeper = ctx.accounts.admin.key(); // Initially admin is keeper
+    state.mint = ctx.accounts.mint.key();
+    state.keepers = [Pubkey::default(); MAX_KEEPERS];
+    state.keepers[0] = ctx.accounts.admin.key(); // Initially admin is sole keeper
+    state.num_keepers = 1;
+    state.publish_threshold = 1;
     state.bump = bump;
     state.sol_vault_bump = ctx.bumps.sol_vault;
     state.current_epoch = 0;
     state.total_sol_distributed = 0;
     state.total_token_distributed = 0;
     state.paused = false;
+    state.guardian = ctx.accounts.admin.key(); // Initially admin is guardian
+    state.challenge_period = 0;
+    state.last_covered_slot = 0;
 
-    msg!("Distributor initialized with admin: {}", state.admin);
+    msg!(
+        "Distributor for mint {} initialized with admin: {}",
+        state.mint,
+        state.admin
+    );
     
     Ok(())
 }
 
+/// Only the mint authority or the program upgrade authority may create a mint's
+/// instance, so nobody else can squat its PDA and become admin
+fn authorize_creator(
+    mint: &Mint,
+    authority: &Pubkey,
+    program_data: Option<&Account<ProgramData>>,
+) -> Result<()> {
+    if mint.mint_authority == COption::Some(*authority) {
+        return Ok(());
+    }
 
+    let program_data = program_data.ok_or(DistributorError::Unauthorized)?;
+    let (expected, _) = Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::ID);
+    require!(
+        program_data.key() == expected
+            && program_data.upgrade_authority_address == Some(*authority),
+        DistributorError::Unauthorized
+    );
+    Ok(())
+}
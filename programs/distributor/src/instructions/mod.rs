@@ -3,11 +3,19 @@ pub mod publish_epoch;
 pub mod claim;
 pub mod fund_vault;
 pub mod admin;
+pub mod close;
+pub mod challenge;
+pub mod sponsor;
+pub mod reward_mint;
+pub mod voucher;
 
 pub use initialize::*;
 pub use publish_epoch::*;
 pub use claim::*;
 pub use fund_vault::*;
 pub use admin::*;
-
-
+pub use close::*;
+pub use challenge::*;
+pub use sponsor::*;
+pub use reward_mint::*;
+pub use voucher::*;
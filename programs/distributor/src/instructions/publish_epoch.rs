@@ -1,13 +1,14 @@
 use anchor_lang::prelude::*;
-use crate::state::{DistributorState, EpochState};
+use crate::state::{DistributorState, EpochState, EpochStatus, MAX_METADATA_URI_LEN};
 use crate::error::DistributorError;
+use crate::{EpochMetadata, VestingSchedule, CURRENT_LEAF_VERSION};
 
 #[derive(Accounts)]
 #[instruction(epoch_id: u64)]
 pub struct PublishEpoch<'info> {
     #[account(
         mut,
-        seeds = [b"distributor_state"],
+        seeds = [b"distributor_state", state.mint.as_ref()],
         bump = state.bump,
         constraint = !state.paused @ DistributorError::Paused
     )]
@@ -17,20 +18,23 @@ pub struct PublishEpoch<'info> {
         init,
         payer = keeper,
         space = EpochState::LEN,
-        seeds = [b"epoch", &epoch_id.to_le_bytes()],
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
         bump
     )]
     pub epoch: Account<'info, EpochState>,
 
     #[account(
         mut,
-        constraint = keeper.key() == state.keeper || keeper.key() == state.admin @ DistributorError::Unauthorized
+        constraint = state.is_keeper(&keeper.key()) @ DistributorError::Unauthorized
     )]
     pub keeper: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
+/// Publish a new epoch. The signing keeper's submission counts as the first
+/// attestation; with a threshold above one the epoch waits in `Attesting`.
+#[allow(clippy::too_many_arguments)]
 pub fn handler(
     ctx: Context<PublishEpoch>,
     epoch_id: u64,
@@ -39,6 +43,10 @@ pub fn handler(
     total_rewards_token: u64,
     start_slot: u64,
     end_slot: u64,
+    leaf_version: u8,
+    metadata: EpochMetadata,
+    vesting: VestingSchedule,
+    declare_gap: bool,
 ) -> Result<()> {
     let state = &mut ctx.accounts.state;
     let epoch = &mut ctx.accounts.epoch;
@@ -54,6 +62,35 @@ pub fn handler(
         end_slot >= start_slot,
         DistributorError::InvalidSlotRange
     );
+    require!(
+        end_slot <= Clock::get()?.slot,
+        DistributorError::SlotRangeInFuture
+    );
+
+    // Epochs must tile the slot line: no overlap with covered slots, and any
+    // skipped slots must be declared explicitly
+    let next_slot = state
+        .last_covered_slot
+        .checked_add(1)
+        .ok_or(DistributorError::MathOverflow)?;
+    let gap_slots = if declare_gap {
+        require!(start_slot > next_slot, DistributorError::NonContiguousSlotRange);
+        start_slot - next_slot
+    } else {
+        require!(start_slot == next_slot, DistributorError::NonContiguousSlotRange);
+        0
+    };
+
+    // Validate leaf hashing scheme
+    require!(
+        leaf_version <= CURRENT_LEAF_VERSION,
+        DistributorError::UnsupportedLeafVersion
+    );
+
+    validate_metadata(&metadata)?;
+    require!(vesting.validate(), DistributorError::InvalidVestingSchedule);
+
+    let now = Clock::get()?.unix_timestamp;
 
     // Initialize epoch state
     epoch.epoch_id = epoch_id;
@@ -64,21 +101,126 @@ pub fn handler(
     epoch.claimed_token = 0;
     epoch.start_slot = start_slot;
     epoch.end_slot = end_slot;
-    epoch.publish_time = Clock::get()?.unix_timestamp;
+    epoch.publish_time = now;
     epoch.num_claimants = 0;
     epoch.bump = ctx.bumps.epoch;
+    epoch.publisher = ctx.accounts.keeper.key();
+    epoch.finalized = false;
+    epoch.finalized_at = 0;
+    epoch.num_receipts_closed = 0;
+    epoch.leaf_version = leaf_version;
+    epoch.metadata = metadata;
+    epoch.gap_slots = gap_slots;
+    epoch.vesting = vesting;
+    epoch.reset_attestations(state.publish_threshold);
+    epoch.record_attestation(ctx.accounts.keeper.key(), now, state.challenge_period)?;
 
     // Update global state
     state.current_epoch = epoch_id;
+    state.last_covered_slot = end_slot;
 
     msg!(
-        "Epoch {} published: {} SOL, {} tokens available",
+        "Epoch {} published: {} SOL, {} tokens available (leaf v{}), {}/{} attestations",
         epoch_id,
         total_rewards_sol,
-        total_rewards_token
+        total_rewards_token,
+        leaf_version,
+        epoch.num_attestations,
+        epoch.required_attestations
+    );
+    msg!(
+        "Epoch {} covers slots {}-{} ({} gap slots declared)",
+        epoch_id,
+        start_slot,
+        end_slot,
+        gap_slots
+    );
+    msg!(
+        "Epoch {} dataset: {} leaves, methodology v{}, {}",
+        epoch_id,
+        epoch.metadata.leaf_count,
+        epoch.metadata.methodology_version,
+        epoch.metadata.uri
     );
 
     Ok(())
 }
 
+/// Validate the allocation dataset commitment supplied with a root
+pub fn validate_metadata(metadata: &EpochMetadata) -> Result<()> {
+    require!(
+        metadata.uri.len() <= MAX_METADATA_URI_LEN,
+        DistributorError::InvalidMetadata
+    );
+    require!(metadata.leaf_count > 0, DistributorError::InvalidMetadata);
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct AttestEpoch<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = !state.paused @ DistributorError::Paused
+    )]
+    pub state: Account<'info, DistributorState>,
 
+    #[account(
+        mut,
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump,
+        constraint = epoch.status == EpochStatus::Attesting @ DistributorError::NotAttesting
+    )]
+    pub epoch: Account<'info, EpochState>,
+
+    #[account(
+        constraint = state.is_keeper(&keeper.key()) @ DistributorError::Unauthorized
+    )]
+    pub keeper: Signer<'info>,
+}
+
+/// Attest a pending epoch. The keeper resubmits the root, totals, dataset
+/// commitment and vesting schedule it built independently; any disagreement
+/// is rejected and leaves the epoch pending.
+#[allow(clippy::too_many_arguments)]
+pub fn attest_handler(
+    ctx: Context<AttestEpoch>,
+    epoch_id: u64,
+    merkle_root: [u8; 32],
+    total_rewards_sol: u64,
+    total_rewards_token: u64,
+    start_slot: u64,
+    end_slot: u64,
+    leaf_version: u8,
+    metadata: EpochMetadata,
+    vesting: VestingSchedule,
+) -> Result<()> {
+    let state = &ctx.accounts.state;
+    let epoch = &mut ctx.accounts.epoch;
+
+    require!(
+        epoch.merkle_root == merkle_root
+            && epoch.total_rewards_sol == total_rewards_sol
+            && epoch.total_rewards_token == total_rewards_token
+            && epoch.start_slot == start_slot
+            && epoch.end_slot == end_slot
+            && epoch.leaf_version == leaf_version
+            && epoch.metadata == metadata
+            && epoch.vesting == vesting,
+        DistributorError::AttestationMismatch
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    epoch.record_attestation(ctx.accounts.keeper.key(), now, state.challenge_period)?;
+
+    msg!(
+        "Epoch {} attested by {}: {}/{} attestations",
+        epoch_id,
+        ctx.accounts.keeper.key(),
+        epoch.num_attestations,
+        epoch.required_attestations
+    );
+
+    Ok(())
+}
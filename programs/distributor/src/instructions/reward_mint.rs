@@ -0,0 +1,474 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer as TokenTransfer};
+use crate::state::{
+    DistributorState, EpochState, EpochStatus, RewardMint, EpochMintRewards, MintClaimReceipt,
+    RECEIPT_CLOSE_GRACE_PERIOD,
+};
+use crate::error::DistributorError;
+use crate::{verify_proof, compute_mint_leaf, LEAF_VERSION_V1};
+
+#[derive(Accounts)]
+pub struct RegisterRewardMint<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = RewardMint::LEN,
+        seeds = [b"reward_mint", state.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub reward_mint: Account<'info, RewardMint>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"reward_vault", state.key().as_ref(), mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = reward_mint
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn register_handler(ctx: Context<RegisterRewardMint>) -> Result<()> {
+    let reward_mint = &mut ctx.accounts.reward_mint;
+    reward_mint.mint = ctx.accounts.mint.key();
+    reward_mint.vault = ctx.accounts.reward_vault.key();
+    reward_mint.enabled = true;
+    reward_mint.total_distributed = 0;
+    reward_mint.bump = ctx.bumps.reward_mint;
+    reward_mint.vault_bump = ctx.bumps.reward_vault;
+
+    msg!(
+        "Reward mint {} registered with vault {}",
+        reward_mint.mint,
+        reward_mint.vault
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetRewardMintEnabled<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_mint", state.key().as_ref(), reward_mint.mint.as_ref()],
+        bump = reward_mint.bump
+    )]
+    pub reward_mint: Account<'info, RewardMint>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn set_enabled_handler(ctx: Context<SetRewardMintEnabled>, enabled: bool) -> Result<()> {
+    let reward_mint = &mut ctx.accounts.reward_mint;
+    reward_mint.enabled = enabled;
+
+    msg!("Reward mint {} enabled set to: {}", reward_mint.mint, enabled);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct PublishEpochMint<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = !state.paused @ DistributorError::Paused
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump,
+        constraint = !epoch.finalized @ DistributorError::EpochFinalized,
+        constraint = epoch.status != EpochStatus::Vetoed @ DistributorError::EpochVetoed
+    )]
+    pub epoch: Account<'info, EpochState>,
+
+    #[account(
+        seeds = [b"reward_mint", state.key().as_ref(), reward_mint.mint.as_ref()],
+        bump = reward_mint.bump,
+        constraint = reward_mint.enabled @ DistributorError::RewardMintDisabled
+    )]
+    pub reward_mint: Account<'info, RewardMint>,
+
+    #[account(
+        init,
+        payer = keeper,
+        space = EpochMintRewards::LEN,
+        seeds = [b"epoch_mint", state.key().as_ref(), &epoch_id.to_le_bytes(), reward_mint.mint.as_ref()],
+        bump
+    )]
+    pub epoch_mint: Account<'info, EpochMintRewards>,
+
+    #[account(
+        mut,
+        constraint = state.is_keeper(&keeper.key()) @ DistributorError::Unauthorized
+    )]
+    pub keeper: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Declare the total of a reward mint for an epoch. Per-wallet amounts come from
+/// mint leaves in the epoch's attested root; the total caps what can be claimed.
+pub fn publish_handler(ctx: Context<PublishEpochMint>, epoch_id: u64, total_rewards: u64) -> Result<()> {
+    require!(
+        ctx.accounts.epoch.leaf_version == LEAF_VERSION_V1,
+        DistributorError::MintLeavesUnsupported
+    );
+    // Totals declared after the challenge period could never be vetoed
+    require!(
+        ctx.accounts.epoch.is_challengeable(Clock::get()?.unix_timestamp),
+        DistributorError::ChallengePeriodEnded
+    );
+
+    let epoch_mint = &mut ctx.accounts.epoch_mint;
+    epoch_mint.epoch_id = epoch_id;
+    epoch_mint.mint = ctx.accounts.reward_mint.mint;
+    epoch_mint.total_rewards = total_rewards;
+    epoch_mint.claimed = 0;
+    epoch_mint.num_claimants = 0;
+    epoch_mint.num_receipts_closed = 0;
+    epoch_mint.publisher = ctx.accounts.keeper.key();
+    epoch_mint.bump = ctx.bumps.epoch_mint;
+
+    let epoch = &mut ctx.accounts.epoch;
+    epoch.num_reward_mints = epoch
+        .num_reward_mints
+        .checked_add(1)
+        .ok_or(DistributorError::MathOverflow)?;
+
+    msg!(
+        "Epoch {} distributes {} of mint {}",
+        epoch_id,
+        total_rewards,
+        epoch_mint.mint
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct ClaimMint<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = !state.paused @ DistributorError::Paused
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump,
+        constraint = !epoch.finalized @ DistributorError::EpochFinalized
+    )]
+    pub epoch: Account<'info, EpochState>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_mint", state.key().as_ref(), reward_mint.mint.as_ref()],
+        bump = reward_mint.bump
+    )]
+    pub reward_mint: Account<'info, RewardMint>,
+
+    #[account(
+        mut,
+        address = reward_mint.vault
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch_mint", state.key().as_ref(), &epoch_id.to_le_bytes(), reward_mint.mint.as_ref()],
+        bump = epoch_mint.bump
+    )]
+    pub epoch_mint: Account<'info, EpochMintRewards>,
+
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        space = MintClaimReceipt::LEN,
+        seeds = [b"mint_claim", state.key().as_ref(), &epoch_id.to_le_bytes(), reward_mint.mint.as_ref(), claimant.key().as_ref()],
+        bump
+    )]
+    pub mint_receipt: Account<'info, MintClaimReceipt>,
+
+    /// Payout token account chosen by the leaf owner
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == reward_mint.mint @ DistributorError::Unauthorized
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn claim_handler(
+    ctx: Context<ClaimMint>,
+    epoch_id: u64,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let epoch = &ctx.accounts.epoch;
+    let mint = ctx.accounts.reward_mint.mint;
+    let wallet = ctx.accounts.claimant.key();
+    let now = Clock::get()?.unix_timestamp;
+
+    // Root must have survived its challenge period
+    require!(epoch.is_claimable(now), DistributorError::EpochNotClaimable);
+    require!(
+        epoch.leaf_version == LEAF_VERSION_V1,
+        DistributorError::MintLeavesUnsupported
+    );
+
+    // Verify the mint leaf against the epoch root
+    let leaf = compute_mint_leaf(epoch.leaf_version, &wallet, epoch_id, &mint, amount);
+    require!(
+        verify_proof(epoch.leaf_version, &proof, epoch.merkle_root, leaf),
+        DistributorError::InvalidProof
+    );
+
+    // First claim records the allocation; later claims release the remaining vested balance
+    let receipt = &mut ctx.accounts.mint_receipt;
+    let is_first_claim = receipt.wallet == Pubkey::default();
+    if is_first_claim {
+        receipt.wallet = wallet;
+        receipt.epoch_id = epoch_id;
+        receipt.mint = mint;
+        receipt.amount = amount;
+        receipt.rent_payer = wallet;
+        receipt.bump = ctx.bumps.mint_receipt;
+    } else {
+        require!(receipt.amount == amount, DistributorError::InvalidProof);
+    }
+    require!(receipt.released < receipt.amount, DistributorError::AlreadyClaimed);
+
+    let release = epoch
+        .vesting
+        .vested_amount(amount, now)
+        .checked_sub(receipt.released)
+        .ok_or(DistributorError::MathOverflow)?;
+    require!(release > 0, DistributorError::NothingVested);
+
+    // Enforce the epoch total for this mint
+    let epoch_mint = &mut ctx.accounts.epoch_mint;
+    epoch_mint.claimed = epoch_mint
+        .claimed
+        .checked_add(release)
+        .ok_or(DistributorError::MathOverflow)?;
+    require!(
+        epoch_mint.claimed <= epoch_mint.total_rewards,
+        DistributorError::EpochTotalsExceeded
+    );
+    if is_first_claim {
+        epoch_mint.num_claimants = epoch_mint
+            .num_claimants
+            .checked_add(1)
+            .ok_or(DistributorError::MathOverflow)?;
+    }
+
+    require!(
+        ctx.accounts.reward_vault.amount >= release,
+        DistributorError::InsufficientBalance
+    );
+
+    // Transfer tokens from the mint's vault, signed by the reward mint PDA
+    let state_key = ctx.accounts.state.key();
+    let bump = [ctx.accounts.reward_mint.bump];
+    let seeds = &[b"reward_mint".as_ref(), state_key.as_ref(), mint.as_ref(), &bump];
+    let signer_seeds = &[&seeds[..]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TokenTransfer {
+                from: ctx.accounts.reward_vault.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: ctx.accounts.reward_mint.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        release,
+    )?;
+
+    let reward_mint = &mut ctx.accounts.reward_mint;
+    reward_mint.total_distributed = reward_mint
+        .total_distributed
+        .checked_add(release)
+        .ok_or(DistributorError::MathOverflow)?;
+
+    receipt.released = receipt
+        .released
+        .checked_add(release)
+        .ok_or(DistributorError::MathOverflow)?;
+    receipt.claimed_at = now;
+
+    msg!(
+        "Claimed {} of mint {} for epoch {} by {} ({}/{} released)",
+        release,
+        mint,
+        epoch_id,
+        wallet,
+        receipt.released,
+        receipt.amount
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct CloseMintClaimReceipt<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump,
+        constraint = epoch.finalized @ DistributorError::EpochNotFinalized
+    )]
+    pub epoch: Account<'info, EpochState>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch_mint", state.key().as_ref(), &epoch_id.to_le_bytes(), mint_receipt.mint.as_ref()],
+        bump = epoch_mint.bump
+    )]
+    pub epoch_mint: Account<'info, EpochMintRewards>,
+
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [b"mint_claim", state.key().as_ref(), &epoch_id.to_le_bytes(), mint_receipt.mint.as_ref(), mint_receipt.wallet.as_ref()],
+        bump = mint_receipt.bump,
+        constraint = authority.key() == mint_receipt.wallet
+            || authority.key() == mint_receipt.rent_payer
+            || authority.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub mint_receipt: Account<'info, MintClaimReceipt>,
+
+    /// CHECK: Rent destination, must be the account that paid for the receipt
+    #[account(
+        mut,
+        address = mint_receipt.rent_payer @ DistributorError::Unauthorized
+    )]
+    pub rent_payer: AccountInfo<'info>,
+
+    /// Leaf owner or rent payer, or the admin once the grace period has passed
+    pub authority: Signer<'info>,
+}
+
+pub fn close_mint_receipt_handler(ctx: Context<CloseMintClaimReceipt>, epoch_id: u64) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    let receipt = &ctx.accounts.mint_receipt;
+    if authority != receipt.wallet && authority != receipt.rent_payer {
+        // Admin sweeps receipts holders never closed so the epoch mint can settle
+        require!(
+            Clock::get()?.unix_timestamp
+                >= ctx.accounts.epoch.finalized_at.saturating_add(RECEIPT_CLOSE_GRACE_PERIOD),
+            DistributorError::ReceiptGracePeriod
+        );
+    }
+
+    let epoch_mint = &mut ctx.accounts.epoch_mint;
+    epoch_mint.num_receipts_closed = epoch_mint
+        .num_receipts_closed
+        .checked_add(1)
+        .ok_or(DistributorError::MathOverflow)?;
+
+    msg!(
+        "Mint claim receipt of {} for epoch {} closed, rent returned to {}",
+        ctx.accounts.mint_receipt.wallet,
+        epoch_id,
+        ctx.accounts.rent_payer.key()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct CloseEpochMint<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = state.is_keeper(&authority.key()) || authority.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump,
+        constraint = epoch.finalized || epoch.status == EpochStatus::Vetoed @ DistributorError::EpochNotFinalized
+    )]
+    pub epoch: Account<'info, EpochState>,
+
+    #[account(
+        mut,
+        close = publisher,
+        seeds = [b"epoch_mint", state.key().as_ref(), &epoch_id.to_le_bytes(), epoch_mint.mint.as_ref()],
+        bump = epoch_mint.bump,
+        constraint = epoch_mint.num_receipts_closed == epoch_mint.num_claimants @ DistributorError::EpochNotSettled
+    )]
+    pub epoch_mint: Account<'info, EpochMintRewards>,
+
+    /// CHECK: Rent destination, must be the keeper that declared the mint totals
+    #[account(
+        mut,
+        address = epoch_mint.publisher @ DistributorError::Unauthorized
+    )]
+    pub publisher: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn close_epoch_mint_handler(ctx: Context<CloseEpochMint>, epoch_id: u64) -> Result<()> {
+    let epoch = &mut ctx.accounts.epoch;
+    epoch.num_reward_mints = epoch
+        .num_reward_mints
+        .checked_sub(1)
+        .ok_or(DistributorError::MathOverflow)?;
+
+    msg!(
+        "Epoch {} reward mint {} closed, rent returned to {}",
+        epoch_id,
+        ctx.accounts.epoch_mint.mint,
+        ctx.accounts.publisher.key()
+    );
+
+    Ok(())
+}
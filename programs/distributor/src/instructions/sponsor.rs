@@ -0,0 +1,329 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use crate::state::{DistributorState, EpochState, ClaimReceipt, SponsorPool, SponsorUsage};
+use crate::error::DistributorError;
+use crate::instructions::claim::{process_claim, ClaimAccounts};
+use crate::{SponsorConfig, SubtreeSums};
+
+#[derive(Accounts)]
+pub struct InitializeSponsorPool<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = SponsorPool::LEN,
+        seeds = [b"sponsor_pool", state.key().as_ref()],
+        bump
+    )]
+    pub sponsor_pool: Account<'info, SponsorPool>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_handler(ctx: Context<InitializeSponsorPool>, config: SponsorConfig) -> Result<()> {
+    let pool = &mut ctx.accounts.sponsor_pool;
+    pool.config = config;
+    pool.total_sponsored_claims = 0;
+    pool.total_lamports_reimbursed = 0;
+    pool.bump = ctx.bumps.sponsor_pool;
+
+    msg!(
+        "Sponsor pool initialized: claims up to {} lamports and {} tokens, {} per wallet, {} per epoch",
+        config.max_claim_lamports,
+        config.max_claim_token,
+        config.max_claims_per_wallet,
+        config.max_claims_per_epoch
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateSponsorPool<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsor_pool", state.key().as_ref()],
+        bump = sponsor_pool.bump
+    )]
+    pub sponsor_pool: Account<'info, SponsorPool>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn update_handler(ctx: Context<UpdateSponsorPool>, config: SponsorConfig) -> Result<()> {
+    ctx.accounts.sponsor_pool.config = config;
+
+    msg!(
+        "Sponsor pool updated: claims up to {} lamports and {} tokens, {} fee reimbursement, {} per wallet, {} per epoch",
+        config.max_claim_lamports,
+        config.max_claim_token,
+        config.fee_reimbursement_lamports,
+        config.max_claims_per_wallet,
+        config.max_claims_per_epoch
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FundSponsorPool<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsor_pool", state.key().as_ref()],
+        bump = sponsor_pool.bump
+    )]
+    pub sponsor_pool: Account<'info, SponsorPool>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn fund_handler(ctx: Context<FundSponsorPool>, amount: u64) -> Result<()> {
+    require!(amount > 0, DistributorError::ZeroAmount);
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.sponsor_pool.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    msg!("Funded sponsor pool with {} lamports", amount);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct ClaimSponsored<'info> {
+    #[account(
+        mut,
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = !state.paused @ DistributorError::Paused
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = epoch.bump,
+        constraint = !epoch.finalized @ DistributorError::EpochFinalized
+    )]
+    pub epoch: Account<'info, EpochState>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = ClaimReceipt::LEN,
+        seeds = [b"claim", state.key().as_ref(), &epoch_id.to_le_bytes(), wallet.key().as_ref()],
+        bump
+    )]
+    pub claim_receipt: Account<'info, ClaimReceipt>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsor_pool", state.key().as_ref()],
+        bump = sponsor_pool.bump
+    )]
+    pub sponsor_pool: Account<'info, SponsorPool>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = SponsorUsage::LEN,
+        seeds = [b"sponsor_usage", state.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub sponsor_usage: Account<'info, SponsorUsage>,
+
+    /// CHECK: SOL vault PDA that holds rewards
+    #[account(
+        mut,
+        seeds = [b"sol_vault", state.key().as_ref()],
+        bump = state.sol_vault_bump
+    )]
+    pub sol_vault: AccountInfo<'info>,
+
+    /// CHECK: Leaf owner; rewards are always paid to this wallet
+    #[account(mut)]
+    pub wallet: AccountInfo<'info>,
+
+    /// Submits the claim and fronts rent; reimbursed by the sponsor pool
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn claim_sponsored_handler(
+    ctx: Context<ClaimSponsored>,
+    epoch_id: u64,
+    amount_sol: u64,
+    amount_token: u64,
+    proof: Vec<[u8; 32]>,
+    proof_sums: Vec<SubtreeSums>,
+) -> Result<()> {
+    let config = ctx.accounts.sponsor_pool.config;
+    let rent = Rent::get()?;
+
+    // Only a wallet's first, small claim of an epoch is sponsored
+    require!(
+        ctx.accounts.claim_receipt.wallet == Pubkey::default(),
+        DistributorError::SponsorshipIneligible
+    );
+    require!(
+        amount_sol <= config.max_claim_lamports && amount_token <= config.max_claim_token,
+        DistributorError::SponsorshipIneligible
+    );
+
+    let usage = &mut ctx.accounts.sponsor_usage;
+    let mut reimbursement = rent.minimum_balance(ClaimReceipt::LEN);
+    if usage.wallet == Pubkey::default() {
+        usage.wallet = ctx.accounts.wallet.key();
+        usage.sponsored_claims = 0;
+        usage.bump = ctx.bumps.sponsor_usage;
+        usage.open_receipts = 0;
+        reimbursement = reimbursement
+            .checked_add(rent.minimum_balance(SponsorUsage::LEN))
+            .ok_or(DistributorError::MathOverflow)?;
+    }
+    reimbursement = reimbursement
+        .checked_add(config.fee_reimbursement_lamports)
+        .ok_or(DistributorError::MathOverflow)?;
+
+    // Per-wallet and per-epoch caps
+    require!(
+        usage.sponsored_claims < config.max_claims_per_wallet,
+        DistributorError::SponsorshipCapReached
+    );
+    require!(
+        ctx.accounts.epoch.num_sponsored_claims < config.max_claims_per_epoch,
+        DistributorError::SponsorshipCapReached
+    );
+    usage.sponsored_claims += 1;
+    usage.open_receipts += 1;
+    ctx.accounts.epoch.num_sponsored_claims += 1;
+
+    // The pool becomes the receipt's rent payer, so closing the receipt refunds the pool
+    let sponsor_pool_key = ctx.accounts.sponsor_pool.key();
+    process_claim(
+        ClaimAccounts {
+            state: &mut ctx.accounts.state,
+            epoch: &mut ctx.accounts.epoch,
+            claim_receipt: &mut ctx.accounts.claim_receipt,
+            receipt_bump: ctx.bumps.claim_receipt,
+            sol_vault: &ctx.accounts.sol_vault,
+            recipient: &ctx.accounts.wallet,
+            wallet: ctx.accounts.wallet.key(),
+            rent_payer: sponsor_pool_key,
+        },
+        epoch_id,
+        amount_sol,
+        amount_token,
+        proof,
+        proof_sums,
+        None,
+    )?;
+
+    // Reimburse the relayer, keeping the pool itself rent-exempt
+    let pool_info = ctx.accounts.sponsor_pool.to_account_info();
+    let available = pool_info
+        .lamports()
+        .saturating_sub(rent.minimum_balance(SponsorPool::LEN));
+    require!(
+        available >= reimbursement,
+        DistributorError::InsufficientBalance
+    );
+    **pool_info.try_borrow_mut_lamports()? -= reimbursement;
+    **ctx.accounts.relayer.try_borrow_mut_lamports()? += reimbursement;
+
+    let pool = &mut ctx.accounts.sponsor_pool;
+    pool.total_sponsored_claims = pool
+        .total_sponsored_claims
+        .checked_add(1)
+        .ok_or(DistributorError::MathOverflow)?;
+    pool.total_lamports_reimbursed = pool
+        .total_lamports_reimbursed
+        .checked_add(reimbursement)
+        .ok_or(DistributorError::MathOverflow)?;
+
+    msg!(
+        "Sponsored claim for {} in epoch {}: {} lamports reimbursed to {}",
+        ctx.accounts.wallet.key(),
+        epoch_id,
+        reimbursement,
+        ctx.accounts.relayer.key()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseSponsorUsage<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    /// Paid for the usage account, so its rent goes back here
+    #[account(
+        mut,
+        seeds = [b"sponsor_pool", state.key().as_ref()],
+        bump = sponsor_pool.bump
+    )]
+    pub sponsor_pool: Account<'info, SponsorPool>,
+
+    #[account(
+        mut,
+        close = sponsor_pool,
+        seeds = [b"sponsor_usage", state.key().as_ref(), sponsor_usage.wallet.as_ref()],
+        bump = sponsor_usage.bump,
+        constraint = sponsor_usage.open_receipts == 0 @ DistributorError::SponsoredReceiptsOpen,
+        constraint = authority.key() == sponsor_usage.wallet
+            || authority.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub sponsor_usage: Account<'info, SponsorUsage>,
+
+    /// Sponsored wallet or admin
+    pub authority: Signer<'info>,
+}
+
+pub fn close_usage_handler(ctx: Context<CloseSponsorUsage>) -> Result<()> {
+    msg!(
+        "Sponsor usage of {} closed after {} sponsored claims, rent returned to the pool",
+        ctx.accounts.sponsor_usage.wallet,
+        ctx.accounts.sponsor_usage.sponsored_claims
+    );
+
+    Ok(())
+}
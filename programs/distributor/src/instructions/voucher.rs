@@ -0,0 +1,436 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as sysvar_instructions, load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_spl::token::{self, Token, TokenAccount, Transfer as TokenTransfer};
+use crate::state::{DistributorState, RewardMint, VoucherBudget, VoucherNonce};
+use crate::error::DistributorError;
+use crate::Voucher;
+
+/// Ed25519 instruction header: signature count, padding, one offsets struct
+const ED25519_HEADER_LEN: usize = 2 + 14;
+/// Instruction index the Ed25519 program uses for "this instruction"
+const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+#[derive(Accounts)]
+pub struct SetVoucherSigner<'info> {
+    #[account(
+        mut,
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn set_signer_handler(ctx: Context<SetVoucherSigner>, voucher_signer: Pubkey) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    let old_signer = state.voucher_signer;
+    state.voucher_signer = voucher_signer;
+
+    msg!("Voucher signer updated from {} to {}", old_signer, voucher_signer);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct OpenVoucherEpoch<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = VoucherBudget::LEN,
+        seeds = [b"voucher_budget", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump
+    )]
+    pub voucher_budget: Account<'info, VoucherBudget>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn open_epoch_handler(
+    ctx: Context<OpenVoucherEpoch>,
+    epoch_id: u64,
+    budget_sol: u64,
+    budget_token: u64,
+    expires_at: i64,
+) -> Result<()> {
+    require!(
+        expires_at > Clock::get()?.unix_timestamp,
+        DistributorError::VoucherExpired
+    );
+
+    let budget = &mut ctx.accounts.voucher_budget;
+    budget.epoch_id = epoch_id;
+    budget.budget_sol = budget_sol;
+    budget.budget_token = budget_token;
+    budget.paid_sol = 0;
+    budget.paid_token = 0;
+    budget.num_redeemed = 0;
+    budget.bump = ctx.bumps.voucher_budget;
+    budget.expires_at = expires_at;
+    budget.num_nonces_closed = 0;
+
+    msg!(
+        "Voucher epoch {} opened with budget {} SOL, {} tokens until {}",
+        epoch_id,
+        budget_sol,
+        budget_token,
+        expires_at
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(voucher: Voucher)]
+pub struct ClaimVoucher<'info> {
+    #[account(
+        mut,
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = !state.paused @ DistributorError::Paused,
+        constraint = state.voucher_signer != Pubkey::default() @ DistributorError::VouchersDisabled
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        seeds = [b"voucher_budget", state.key().as_ref(), &voucher.epoch_id.to_le_bytes()],
+        bump = voucher_budget.bump
+    )]
+    pub voucher_budget: Account<'info, VoucherBudget>,
+
+    /// Fails to initialize if the nonce was already redeemed
+    #[account(
+        init,
+        payer = claimant,
+        space = VoucherNonce::LEN,
+        seeds = [b"voucher_nonce", state.key().as_ref(), &voucher.epoch_id.to_le_bytes(), &voucher.nonce.to_le_bytes()],
+        bump
+    )]
+    pub voucher_nonce: Account<'info, VoucherNonce>,
+
+    /// CHECK: SOL vault PDA that holds rewards
+    #[account(
+        mut,
+        seeds = [b"sol_vault", state.key().as_ref()],
+        bump = state.sol_vault_bump
+    )]
+    pub sol_vault: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = claimant.key() == voucher.wallet @ DistributorError::Unauthorized
+    )]
+    pub claimant: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, checked by address
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    /// Registered reward mint of the flywheel token; required when the voucher pays tokens
+    #[account(
+        mut,
+        seeds = [b"reward_mint", state.key().as_ref(), state.mint.as_ref()],
+        bump = reward_mint.bump
+    )]
+    pub reward_mint: Option<Account<'info, RewardMint>>,
+
+    /// Token vault of `reward_mint`; checked in the handler
+    #[account(mut)]
+    pub reward_vault: Option<Account<'info, TokenAccount>>,
+
+    /// Payout token account chosen by the voucher wallet
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == state.mint @ DistributorError::Unauthorized
+    )]
+    pub recipient_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn claim_voucher_handler(ctx: Context<ClaimVoucher>, voucher: Voucher) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= voucher.expiry, DistributorError::VoucherExpired);
+    // Nonces are closable once the budget expires, so no voucher may outlive it
+    require!(
+        voucher.expiry <= ctx.accounts.voucher_budget.expires_at,
+        DistributorError::VoucherExpired
+    );
+
+    let state_key = ctx.accounts.state.key();
+    verify_voucher_signature(
+        &ctx.accounts.instructions_sysvar,
+        &ctx.accounts.state.voucher_signer,
+        &voucher.message(&state_key),
+    )?;
+
+    // Enforce the voucher epoch budget
+    let budget = &mut ctx.accounts.voucher_budget;
+    budget.paid_sol = budget
+        .paid_sol
+        .checked_add(voucher.amount_sol)
+        .ok_or(DistributorError::MathOverflow)?;
+    budget.paid_token = budget
+        .paid_token
+        .checked_add(voucher.amount_token)
+        .ok_or(DistributorError::MathOverflow)?;
+    require!(
+        budget.paid_sol <= budget.budget_sol && budget.paid_token <= budget.budget_token,
+        DistributorError::VoucherBudgetExceeded
+    );
+    budget.num_redeemed = budget
+        .num_redeemed
+        .checked_add(1)
+        .ok_or(DistributorError::MathOverflow)?;
+
+    // Check vault has sufficient balance for SOL
+    let vault_balance = ctx.accounts.sol_vault.lamports();
+    require!(
+        vault_balance >= voucher.amount_sol,
+        DistributorError::InsufficientBalance
+    );
+
+    if voucher.amount_sol > 0 {
+        **ctx.accounts.sol_vault.try_borrow_mut_lamports()? -= voucher.amount_sol;
+        **ctx.accounts.claimant.try_borrow_mut_lamports()? += voucher.amount_sol;
+    }
+
+    if voucher.amount_token > 0 {
+        let (Some(reward_mint), Some(reward_vault), Some(recipient), Some(token_program)) = (
+            ctx.accounts.reward_mint.as_mut(),
+            ctx.accounts.reward_vault.as_ref(),
+            ctx.accounts.recipient_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        ) else {
+            return err!(DistributorError::MissingTokenAccounts);
+        };
+        require!(
+            reward_vault.key() == reward_mint.vault,
+            DistributorError::MissingTokenAccounts
+        );
+        require!(
+            reward_vault.amount >= voucher.amount_token,
+            DistributorError::InsufficientBalance
+        );
+
+        let mint_key = reward_mint.mint;
+        let seeds = &[
+            b"reward_mint".as_ref(),
+            state_key.as_ref(),
+            mint_key.as_ref(),
+            &[reward_mint.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                TokenTransfer {
+                    from: reward_vault.to_account_info(),
+                    to: recipient.to_account_info(),
+                    authority: reward_mint.to_account_info(),
+                },
+                signer,
+            ),
+            voucher.amount_token,
+        )?;
+
+        reward_mint.total_distributed = reward_mint
+            .total_distributed
+            .checked_add(voucher.amount_token)
+            .ok_or(DistributorError::MathOverflow)?;
+    }
+
+    // Update global totals
+    let state = &mut ctx.accounts.state;
+    state.total_sol_distributed = state
+        .total_sol_distributed
+        .checked_add(voucher.amount_sol)
+        .ok_or(DistributorError::MathOverflow)?;
+    state.total_token_distributed = state
+        .total_token_distributed
+        .checked_add(voucher.amount_token)
+        .ok_or(DistributorError::MathOverflow)?;
+
+    let nonce = &mut ctx.accounts.voucher_nonce;
+    nonce.wallet = voucher.wallet;
+    nonce.epoch_id = voucher.epoch_id;
+    nonce.redeemed_at = now;
+    nonce.bump = ctx.bumps.voucher_nonce;
+
+    msg!(
+        "Voucher {} redeemed by {}: {} SOL and {} tokens from voucher epoch {}",
+        voucher.nonce,
+        voucher.wallet,
+        voucher.amount_sol,
+        voucher.amount_token,
+        voucher.epoch_id
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64, nonce: u64)]
+pub struct CloseVoucherNonce<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        seeds = [b"voucher_budget", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = voucher_budget.bump
+    )]
+    pub voucher_budget: Account<'info, VoucherBudget>,
+
+    #[account(
+        mut,
+        close = wallet,
+        seeds = [b"voucher_nonce", state.key().as_ref(), &epoch_id.to_le_bytes(), &nonce.to_le_bytes()],
+        bump = voucher_nonce.bump,
+        constraint = authority.key() == voucher_nonce.wallet
+            || authority.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub voucher_nonce: Account<'info, VoucherNonce>,
+
+    /// CHECK: Rent destination, must be the wallet that redeemed (and paid for) the nonce
+    #[account(
+        mut,
+        address = voucher_nonce.wallet @ DistributorError::Unauthorized
+    )]
+    pub wallet: AccountInfo<'info>,
+
+    /// Redeeming wallet or admin
+    pub authority: Signer<'info>,
+}
+
+pub fn close_nonce_handler(ctx: Context<CloseVoucherNonce>, epoch_id: u64, nonce: u64) -> Result<()> {
+    // Every voucher of the epoch expires with its budget, so a closed nonce
+    // cannot be redeemed again
+    let budget = &mut ctx.accounts.voucher_budget;
+    require!(
+        Clock::get()?.unix_timestamp > budget.expires_at,
+        DistributorError::VoucherEpochActive
+    );
+
+    budget.num_nonces_closed = budget
+        .num_nonces_closed
+        .checked_add(1)
+        .ok_or(DistributorError::MathOverflow)?;
+
+    msg!(
+        "Voucher nonce {} of voucher epoch {} closed, rent returned to {}",
+        nonce,
+        epoch_id,
+        ctx.accounts.wallet.key()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct CloseVoucherBudget<'info> {
+    #[account(
+        seeds = [b"distributor_state", state.mint.as_ref()],
+        bump = state.bump,
+        constraint = admin.key() == state.admin @ DistributorError::Unauthorized
+    )]
+    pub state: Account<'info, DistributorState>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"voucher_budget", state.key().as_ref(), &epoch_id.to_le_bytes()],
+        bump = voucher_budget.bump
+    )]
+    pub voucher_budget: Account<'info, VoucherBudget>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn close_budget_handler(ctx: Context<CloseVoucherBudget>, epoch_id: u64) -> Result<()> {
+    let budget = &ctx.accounts.voucher_budget;
+    require!(
+        Clock::get()?.unix_timestamp > budget.expires_at,
+        DistributorError::VoucherEpochActive
+    );
+    require!(
+        budget.num_nonces_closed == budget.num_redeemed,
+        DistributorError::VoucherNoncesOpen
+    );
+
+    msg!(
+        "Voucher epoch {} closed after {} redemptions",
+        epoch_id,
+        budget.num_redeemed
+    );
+
+    Ok(())
+}
+
+/// Require the previous instruction to be an Ed25519 program check of `message`
+/// signed by `signer`, with all data embedded in that instruction.
+fn verify_voucher_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, DistributorError::InvalidVoucherSignature);
+    let ix = load_instruction_at_checked(current_index as usize - 1, instructions_sysvar)?;
+    require!(
+        ix.program_id == ed25519_program::ID && ix.accounts.is_empty(),
+        DistributorError::InvalidVoucherSignature
+    );
+
+    let data = &ix.data;
+    require!(
+        data.len() >= ED25519_HEADER_LEN && data[0] == 1,
+        DistributorError::InvalidVoucherSignature
+    );
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let signature_ix = read_u16(4);
+    let public_key_offset = read_u16(6) as usize;
+    let public_key_ix = read_u16(8);
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+    let message_ix = read_u16(14);
+    require!(
+        signature_ix == ED25519_CURRENT_INSTRUCTION
+            && public_key_ix == ED25519_CURRENT_INSTRUCTION
+            && message_ix == ED25519_CURRENT_INSTRUCTION,
+        DistributorError::InvalidVoucherSignature
+    );
+
+    let signed_key = data.get(public_key_offset..public_key_offset + 32);
+    let signed_message = data.get(message_offset..message_offset + message_size);
+    require!(
+        signed_key == Some(signer.as_ref()) && signed_message == Some(message),
+        DistributorError::InvalidVoucherSignature
+    );
+
+    Ok(())
+}
@@ -13,12 +13,15 @@ use instructions::*;
 pub mod distributor {
     use super::*;
 
-    /// Initialize the distributor with admin authority
+    /// Initialize a distributor instance for a token mint with admin authority.
+    /// Must be authorized by the mint authority or the program upgrade authority.
     pub fn initialize(ctx: Context<Initialize>, bump: u8) -> Result<()> {
         instructions::initialize::handler(ctx, bump)
     }
 
-    /// Publish a new epoch with merkle root and reward amounts
+    /// Publish a new epoch with merkle root, reward amounts and allocation dataset commitment.
+    /// The slot range must start right after the last covered slot unless `declare_gap` is set.
+    #[allow(clippy::too_many_arguments)]
     pub fn publish_epoch(
         ctx: Context<PublishEpoch>,
         epoch_id: u64,
@@ -27,6 +30,10 @@ pub mod distributor {
         total_rewards_token: u64,
         start_slot: u64,
         end_slot: u64,
+        leaf_version: u8,
+        metadata: EpochMetadata,
+        vesting: VestingSchedule,
+        declare_gap: bool,
     ) -> Result<()> {
         instructions::publish_epoch::handler(
             ctx,
@@ -36,18 +43,215 @@ pub mod distributor {
             total_rewards_token,
             start_slot,
             end_slot,
+            leaf_version,
+            metadata,
+            vesting,
+            declare_gap,
         )
     }
 
-    /// Claim rewards for a specific epoch using merkle proof
+    /// Attest a pending epoch with an independently built root and totals (keeper only)
+    #[allow(clippy::too_many_arguments)]
+    pub fn attest_epoch(
+        ctx: Context<AttestEpoch>,
+        epoch_id: u64,
+        merkle_root: [u8; 32],
+        total_rewards_sol: u64,
+        total_rewards_token: u64,
+        start_slot: u64,
+        end_slot: u64,
+        leaf_version: u8,
+        metadata: EpochMetadata,
+        vesting: VestingSchedule,
+    ) -> Result<()> {
+        instructions::publish_epoch::attest_handler(
+            ctx,
+            epoch_id,
+            merkle_root,
+            total_rewards_sol,
+            total_rewards_token,
+            start_slot,
+            end_slot,
+            leaf_version,
+            metadata,
+            vesting,
+        )
+    }
+
+    /// Veto a root during its challenge period (guardian or admin)
+    pub fn veto_epoch(ctx: Context<VetoEpoch>, epoch_id: u64) -> Result<()> {
+        instructions::challenge::veto_handler(ctx, epoch_id)
+    }
+
+    /// Replace a vetoed root for the same epoch
+    pub fn republish_epoch(
+        ctx: Context<RepublishEpoch>,
+        epoch_id: u64,
+        merkle_root: [u8; 32],
+        total_rewards_sol: u64,
+        total_rewards_token: u64,
+        leaf_version: u8,
+        metadata: EpochMetadata,
+    ) -> Result<()> {
+        instructions::challenge::republish_handler(
+            ctx,
+            epoch_id,
+            merkle_root,
+            total_rewards_sol,
+            total_rewards_token,
+            leaf_version,
+            metadata,
+        )
+    }
+
+    /// Close a vetoed epoch that will not be republished, returning rent to its publisher.
+    /// Its reward mint totals must be closed first (admin only)
+    pub fn close_vetoed_epoch(ctx: Context<CloseVetoedEpoch>, epoch_id: u64) -> Result<()> {
+        instructions::challenge::close_vetoed_handler(ctx, epoch_id)
+    }
+
+    /// Claim rewards for a specific epoch using merkle proof, paying `withdraw_sol` and
+    /// `withdraw_token` out of the vested, unclaimed allocation to `recipient`. The
+    /// allocation may be claimed in parts across calls and recipients. Only SOL is paid out;
+    /// the token side is release accounting, token payouts go through registered reward mints.
+    /// (`proof_sums` carries sibling subtree sums for sum tree epochs, empty otherwise)
+    #[allow(clippy::too_many_arguments)]
     pub fn claim(
         ctx: Context<Claim>,
         epoch_id: u64,
         amount_sol: u64,
         amount_token: u64,
         proof: Vec<[u8; 32]>,
+        proof_sums: Vec<SubtreeSums>,
+        withdraw_sol: u64,
+        withdraw_token: u64,
+    ) -> Result<()> {
+        instructions::claim::handler(
+            ctx,
+            epoch_id,
+            amount_sol,
+            amount_token,
+            proof,
+            proof_sums,
+            withdraw_sol,
+            withdraw_token,
+        )
+    }
+
+    /// Relay a claim on behalf of a wallet; the relayer pays fees and rent,
+    /// everything claimable is paid to the leaf wallet
+    pub fn claim_for(
+        ctx: Context<ClaimFor>,
+        epoch_id: u64,
+        amount_sol: u64,
+        amount_token: u64,
+        proof: Vec<[u8; 32]>,
+        proof_sums: Vec<SubtreeSums>,
+    ) -> Result<()> {
+        instructions::claim::claim_for_handler(ctx, epoch_id, amount_sol, amount_token, proof, proof_sums)
+    }
+
+    /// Relay a small claim whose receipt rent (and optionally fee) is reimbursed by the sponsor pool
+    pub fn claim_sponsored(
+        ctx: Context<ClaimSponsored>,
+        epoch_id: u64,
+        amount_sol: u64,
+        amount_token: u64,
+        proof: Vec<[u8; 32]>,
+        proof_sums: Vec<SubtreeSums>,
     ) -> Result<()> {
-        instructions::claim::handler(ctx, epoch_id, amount_sol, amount_token, proof)
+        instructions::sponsor::claim_sponsored_handler(ctx, epoch_id, amount_sol, amount_token, proof, proof_sums)
+    }
+
+    /// Create the sponsor pool for gasless claims (admin only)
+    pub fn initialize_sponsor_pool(ctx: Context<InitializeSponsorPool>, config: SponsorConfig) -> Result<()> {
+        instructions::sponsor::initialize_handler(ctx, config)
+    }
+
+    /// Update sponsor pool limits (admin only)
+    pub fn update_sponsor_pool(ctx: Context<UpdateSponsorPool>, config: SponsorConfig) -> Result<()> {
+        instructions::sponsor::update_handler(ctx, config)
+    }
+
+    /// Fund the sponsor pool with SOL
+    pub fn fund_sponsor_pool(ctx: Context<FundSponsorPool>, amount: u64) -> Result<()> {
+        instructions::sponsor::fund_handler(ctx, amount)
+    }
+
+    /// Close a wallet's sponsor usage once all its sponsored receipts are closed,
+    /// returning rent to the sponsor pool (wallet or admin)
+    pub fn close_sponsor_usage(ctx: Context<CloseSponsorUsage>) -> Result<()> {
+        instructions::sponsor::close_usage_handler(ctx)
+    }
+
+    /// Register an additional reward mint with its own vault (admin only)
+    pub fn register_reward_mint(ctx: Context<RegisterRewardMint>) -> Result<()> {
+        instructions::reward_mint::register_handler(ctx)
+    }
+
+    /// Enable or disable a registered reward mint (admin only)
+    pub fn set_reward_mint_enabled(ctx: Context<SetRewardMintEnabled>, enabled: bool) -> Result<()> {
+        instructions::reward_mint::set_enabled_handler(ctx, enabled)
+    }
+
+    /// Declare a reward mint's total for an epoch while its root is still challengeable (keeper only)
+    pub fn publish_epoch_mint(ctx: Context<PublishEpochMint>, epoch_id: u64, total_rewards: u64) -> Result<()> {
+        instructions::reward_mint::publish_handler(ctx, epoch_id, total_rewards)
+    }
+
+    /// Claim a reward mint allocation for an epoch using merkle proof
+    pub fn claim_mint(
+        ctx: Context<ClaimMint>,
+        epoch_id: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::reward_mint::claim_handler(ctx, epoch_id, amount, proof)
+    }
+
+    /// Close a reward mint claim receipt of a finalized epoch, returning rent to whoever paid it;
+    /// the admin may close it once the grace period after finalization has passed
+    pub fn close_mint_claim_receipt(ctx: Context<CloseMintClaimReceipt>, epoch_id: u64) -> Result<()> {
+        instructions::reward_mint::close_mint_receipt_handler(ctx, epoch_id)
+    }
+
+    /// Close a settled epoch reward mint, or one of a vetoed epoch, returning rent to its publisher
+    pub fn close_epoch_mint(ctx: Context<CloseEpochMint>, epoch_id: u64) -> Result<()> {
+        instructions::reward_mint::close_epoch_mint_handler(ctx, epoch_id)
+    }
+
+    /// Set the key that signs claim vouchers; `Pubkey::default()` disables vouchers (admin only)
+    pub fn set_voucher_signer(ctx: Context<SetVoucherSigner>, voucher_signer: Pubkey) -> Result<()> {
+        instructions::voucher::set_signer_handler(ctx, voucher_signer)
+    }
+
+    /// Open a voucher epoch with a cap on total voucher payouts; its vouchers must
+    /// expire by `expires_at` (admin only)
+    pub fn open_voucher_epoch(
+        ctx: Context<OpenVoucherEpoch>,
+        epoch_id: u64,
+        budget_sol: u64,
+        budget_token: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::voucher::open_epoch_handler(ctx, epoch_id, budget_sol, budget_token, expires_at)
+    }
+
+    /// Claim with a voucher signed by the voucher signer. The preceding instruction
+    /// must be an Ed25519 program signature check over the voucher message.
+    pub fn claim_voucher(ctx: Context<ClaimVoucher>, voucher: Voucher) -> Result<()> {
+        instructions::voucher::claim_voucher_handler(ctx, voucher)
+    }
+
+    /// Close a redeemed voucher nonce once its voucher epoch has expired, returning
+    /// rent to the redeeming wallet (wallet or admin)
+    pub fn close_voucher_nonce(ctx: Context<CloseVoucherNonce>, epoch_id: u64, nonce: u64) -> Result<()> {
+        instructions::voucher::close_nonce_handler(ctx, epoch_id, nonce)
+    }
+
+    /// Close an expired voucher epoch budget after all its nonces are closed (admin only)
+    pub fn close_voucher_budget(ctx: Context<CloseVoucherBudget>, epoch_id: u64) -> Result<()> {
+        instructions::voucher::close_budget_handler(ctx, epoch_id)
     }
 
     /// Fund the rewards vault with SOL
@@ -60,39 +264,528 @@ pub mod distributor {
         instructions::fund_vault::handler_token(ctx, amount)
     }
 
+    /// Close an epoch to new claims so receipts can be reclaimed (admin only)
+    pub fn finalize_epoch(ctx: Context<FinalizeEpoch>, epoch_id: u64) -> Result<()> {
+        instructions::close::finalize_handler(ctx, epoch_id)
+    }
+
+    /// Close a claim receipt of a finalized epoch, returning rent to whoever paid it
+    pub fn close_claim_receipt(ctx: Context<CloseClaimReceipt>, epoch_id: u64) -> Result<()> {
+        instructions::close::close_receipt_handler(ctx, epoch_id)
+    }
+
+    /// Close a settled epoch, returning rent to its publisher
+    pub fn close_epoch(ctx: Context<CloseEpoch>, epoch_id: u64) -> Result<()> {
+        instructions::close::close_epoch_handler(ctx, epoch_id)
+    }
+
     /// Update admin authority (requires current admin signature)
     pub fn update_admin(ctx: Context<UpdateAdmin>, new_admin: Pubkey) -> Result<()> {
         instructions::admin::update_admin_handler(ctx, new_admin)
     }
 
-    /// Update keeper authority
-    pub fn update_keeper(ctx: Context<UpdateKeeper>, new_keeper: Pubkey) -> Result<()> {
-        instructions::admin::update_keeper_handler(ctx, new_keeper)
+    /// Add a keeper to the publishing set (admin only)
+    pub fn add_keeper(ctx: Context<UpdateKeeper>, keeper: Pubkey) -> Result<()> {
+        instructions::admin::add_keeper_handler(ctx, keeper)
+    }
+
+    /// Remove a keeper from the publishing set (admin only)
+    pub fn remove_keeper(ctx: Context<UpdateKeeper>, keeper: Pubkey) -> Result<()> {
+        instructions::admin::remove_keeper_handler(ctx, keeper)
+    }
+
+    /// Set how many matching keeper attestations publish a root (admin only)
+    pub fn set_publish_threshold(ctx: Context<UpdateKeeper>, threshold: u8) -> Result<()> {
+        instructions::admin::set_publish_threshold_handler(ctx, threshold)
+    }
+
+    /// Update guardian authority (admin only)
+    pub fn update_guardian(ctx: Context<UpdateGuardian>, new_guardian: Pubkey) -> Result<()> {
+        instructions::admin::update_guardian_handler(ctx, new_guardian)
     }
+
+    /// Set the challenge period new roots must wait before becoming claimable (admin only)
+    pub fn set_challenge_period(ctx: Context<SetChallengePeriod>, challenge_period: i64) -> Result<()> {
+        instructions::admin::set_challenge_period_handler(ctx, challenge_period)
+    }
+}
+
+/// Commitment to the off-chain allocation dataset behind an epoch root, so
+/// anyone can fetch the data and recompute the root independently
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq)]
+pub struct EpochMetadata {
+    /// SHA-256 of the full allocation dataset (audit CSV)
+    pub content_hash: [u8; 32],
+    /// Where the allocation dataset can be fetched
+    pub uri: String,
+    /// Number of leaves in the merkle tree
+    pub leaf_count: u32,
+    /// Snapshot/methodology version used to build the allocations
+    pub methodology_version: u16,
+}
+
+/// Sponsor pool limits for gasless claims
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct SponsorConfig {
+    /// Only allocations of at most this many lamports are sponsored
+    pub max_claim_lamports: u64,
+    /// ... and at most this many token base units
+    pub max_claim_token: u64,
+    /// Extra lamports paid to the relayer towards transaction fees (0 = rent only)
+    pub fee_reimbursement_lamports: u64,
+    /// Sponsored claims allowed per wallet across all epochs, until its usage account is closed
+    pub max_claims_per_wallet: u32,
+    /// Sponsored claims allowed per epoch
+    pub max_claims_per_epoch: u32,
+}
+
+/// Linear vesting schedule applied to every allocation of an epoch.
+/// A zero `duration_seconds` releases the full allocation immediately.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct VestingSchedule {
+    /// Unix timestamp vesting starts from
+    pub start_time: i64,
+    /// Seconds after `start_time` before anything can be released
+    pub cliff_seconds: i64,
+    /// Seconds after `start_time` until the allocation is fully vested
+    pub duration_seconds: i64,
+}
+
+impl VestingSchedule {
+    pub fn validate(&self) -> bool {
+        self.cliff_seconds >= 0
+            && self.duration_seconds >= 0
+            && self.cliff_seconds <= self.duration_seconds
+    }
+
+    /// Every allocation fully vested at `now`
+    pub fn is_complete(&self, now: i64) -> bool {
+        self.duration_seconds == 0 || now >= self.start_time.saturating_add(self.duration_seconds)
+    }
+
+    /// Portion of `total` vested at `now`
+    pub fn vested_amount(&self, total: u64, now: i64) -> u64 {
+        if self.duration_seconds == 0 {
+            return total;
+        }
+        let elapsed = now.saturating_sub(self.start_time);
+        if elapsed < self.cliff_seconds {
+            return 0;
+        }
+        if elapsed >= self.duration_seconds {
+            return total;
+        }
+        ((total as u128) * (elapsed as u128) / (self.duration_seconds as u128)) as u64
+    }
+}
+
+/// Off-chain signed payout authorization for ad-hoc campaigns
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct Voucher {
+    /// Wallet allowed to redeem the voucher
+    pub wallet: Pubkey,
+    /// Voucher epoch whose budget the payout counts against
+    pub epoch_id: u64,
+    pub amount_sol: u64,
+    pub amount_token: u64,
+    /// Unix timestamp after which the voucher can no longer be redeemed
+    pub expiry: i64,
+    /// Unique per voucher epoch; a nonce can be redeemed once
+    pub nonce: u64,
+}
+
+impl Voucher {
+    /// Bytes the voucher signer signs, bound to a single distributor instance
+    pub fn message(&self, distributor: &Pubkey) -> Vec<u8> {
+        let mut message = Vec::with_capacity(VOUCHER_DOMAIN.len() + 104);
+        message.extend_from_slice(VOUCHER_DOMAIN);
+        message.extend_from_slice(distributor.as_ref());
+        message.extend_from_slice(self.wallet.as_ref());
+        message.extend_from_slice(&self.epoch_id.to_le_bytes());
+        message.extend_from_slice(&self.amount_sol.to_le_bytes());
+        message.extend_from_slice(&self.amount_token.to_le_bytes());
+        message.extend_from_slice(&self.expiry.to_le_bytes());
+        message.extend_from_slice(&self.nonce.to_le_bytes());
+        message
+    }
+}
+
+/// SOL and token totals committed to by a sum tree node
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct SubtreeSums {
+    pub sol: u64,
+    pub token: u64,
 }
 
-/// Verify merkle proof for a leaf
-pub fn verify_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+impl SubtreeSums {
+    pub fn checked_add(&self, other: &SubtreeSums) -> Option<SubtreeSums> {
+        Some(SubtreeSums {
+            sol: self.sol.checked_add(other.sol)?,
+            token: self.token.checked_add(other.token)?,
+        })
+    }
+}
+
+/// Leaf scheme of epochs published before domain separation:
+/// `keccak(wallet || epoch_id || amount_sol || amount_token)` with unprefixed nodes
+pub const LEAF_VERSION_LEGACY: u8 = 0;
+/// Domain-separated scheme: leaves and internal nodes are hashed with distinct prefixes
+pub const LEAF_VERSION_V1: u8 = 1;
+/// Merkle sum tree: v1 leaves, internal nodes also commit to subtree SOL and token sums
+pub const LEAF_VERSION_SUM_TREE: u8 = 2;
+/// Highest leaf version accepted by `publish_epoch`
+pub const CURRENT_LEAF_VERSION: u8 = LEAF_VERSION_SUM_TREE;
+
+/// Domain prefix for leaf hashes (v1+)
+pub const LEAF_PREFIX: u8 = 0x00;
+/// Domain prefix for internal node hashes (v1+)
+pub const NODE_PREFIX: u8 = 0x01;
+/// Domain prefix for registered reward mint leaves (v1 trees only)
+pub const MINT_LEAF_PREFIX: u8 = 0x02;
+/// Domain prefix for voucher messages signed by the voucher signer
+pub const VOUCHER_DOMAIN: &[u8] = b"epoch-distributor-voucher-v1";
+
+/// Verify merkle proof for a leaf using the epoch's leaf version
+pub fn verify_proof(leaf_version: u8, proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
     let mut computed_hash = leaf;
     for proof_element in proof.iter() {
-        if computed_hash <= *proof_element {
-            computed_hash = keccak::hashv(&[&computed_hash, proof_element]).0;
-        } else {
-            computed_hash = keccak::hashv(&[proof_element, &computed_hash]).0;
-        }
+        computed_hash = hash_node(leaf_version, &computed_hash, proof_element);
     }
     computed_hash == root
 }
 
-/// Compute leaf hash for claim verification
-pub fn compute_leaf(wallet: &Pubkey, epoch_id: u64, amount_sol: u64, amount_token: u64) -> [u8; 32] {
+/// Hash a pair of sibling nodes in sorted order
+pub fn hash_node(leaf_version: u8, a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    match leaf_version {
+        LEAF_VERSION_LEGACY => keccak::hashv(&[left, right]).0,
+        _ => keccak::hashv(&[&[NODE_PREFIX], left, right]).0,
+    }
+}
+
+/// Verify a merkle sum tree proof. Each proof element carries the sibling's subtree
+/// sums; the sums recomputed at the root must equal the epoch's published totals.
+pub fn verify_sum_proof(
+    proof: &[[u8; 32]],
+    proof_sums: &[SubtreeSums],
+    root: [u8; 32],
+    root_sums: SubtreeSums,
+    leaf: [u8; 32],
+    leaf_sums: SubtreeSums,
+) -> bool {
+    if proof.len() != proof_sums.len() {
+        return false;
+    }
+
+    let mut computed_hash = leaf;
+    let mut computed_sums = leaf_sums;
+    for (proof_element, sibling_sums) in proof.iter().zip(proof_sums.iter()) {
+        computed_hash = hash_sum_node(&computed_hash, &computed_sums, proof_element, sibling_sums);
+        computed_sums = match computed_sums.checked_add(sibling_sums) {
+            Some(sums) => sums,
+            None => return false,
+        };
+    }
+    computed_hash == root && computed_sums == root_sums
+}
+
+/// Hash a pair of sum tree nodes in sorted hash order, committing to both subtree sums
+pub fn hash_sum_node(
+    a: &[u8; 32],
+    a_sums: &SubtreeSums,
+    b: &[u8; 32],
+    b_sums: &SubtreeSums,
+) -> [u8; 32] {
+    let ((left, left_sums), (right, right_sums)) = if a <= b {
+        ((a, a_sums), (b, b_sums))
+    } else {
+        ((b, b_sums), (a, a_sums))
+    };
+    keccak::hashv(&[
+        &[NODE_PREFIX],
+        left,
+        &left_sums.sol.to_le_bytes(),
+        &left_sums.token.to_le_bytes(),
+        right,
+        &right_sums.sol.to_le_bytes(),
+        &right_sums.token.to_le_bytes(),
+    ])
+    .0
+}
+
+/// Compute leaf hash for claim verification using the epoch's leaf version
+pub fn compute_leaf(
+    leaf_version: u8,
+    wallet: &Pubkey,
+    epoch_id: u64,
+    amount_sol: u64,
+    amount_token: u64,
+) -> [u8; 32] {
+    match leaf_version {
+        LEAF_VERSION_LEGACY => keccak::hashv(&[
+            wallet.as_ref(),
+            &epoch_id.to_le_bytes(),
+            &amount_sol.to_le_bytes(),
+            &amount_token.to_le_bytes(),
+        ])
+        .0,
+        _ => keccak::hashv(&[
+            &[LEAF_PREFIX, leaf_version],
+            wallet.as_ref(),
+            &epoch_id.to_le_bytes(),
+            &amount_sol.to_le_bytes(),
+            &amount_token.to_le_bytes(),
+        ])
+        .0,
+    }
+}
+
+/// Compute the leaf hash of a registered reward mint allocation. Mint leaves
+/// live in the same tree as SOL/token leaves and are told apart by their prefix.
+pub fn compute_mint_leaf(
+    leaf_version: u8,
+    wallet: &Pubkey,
+    epoch_id: u64,
+    mint: &Pubkey,
+    amount: u64,
+) -> [u8; 32] {
     keccak::hashv(&[
+        &[MINT_LEAF_PREFIX, leaf_version],
         wallet.as_ref(),
         &epoch_id.to_le_bytes(),
-        &amount_sol.to_le_bytes(),
-        &amount_token.to_le_bytes(),
+        mint.as_ref(),
+        &amount.to_le_bytes(),
     ])
     .0
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallet(seed: u8) -> Pubkey {
+        Pubkey::new_from_array([seed; 32])
+    }
+
+    /// Leaves of a four-wallet epoch and the sorted-pair tree above them
+    fn four_leaf_tree(leaf_version: u8) -> ([[u8; 32]; 4], [u8; 32]) {
+        let leaves = [
+            compute_leaf(leaf_version, &wallet(1), 7, 100, 10),
+            compute_leaf(leaf_version, &wallet(2), 7, 200, 20),
+            compute_leaf(leaf_version, &wallet(3), 7, 300, 30),
+            compute_leaf(leaf_version, &wallet(4), 7, 400, 40),
+        ];
+        let left = hash_node(leaf_version, &leaves[0], &leaves[1]);
+        let right = hash_node(leaf_version, &leaves[2], &leaves[3]);
+        (leaves, hash_node(leaf_version, &left, &right))
+    }
+
+    #[test]
+    fn legacy_leaf_layout() {
+        let expected = keccak::hashv(&[
+            wallet(1).as_ref(),
+            &7u64.to_le_bytes(),
+            &100u64.to_le_bytes(),
+            &10u64.to_le_bytes(),
+        ])
+        .0;
+        assert_eq!(compute_leaf(LEAF_VERSION_LEGACY, &wallet(1), 7, 100, 10), expected);
+    }
+
+    #[test]
+    fn v1_leaf_and_node_layout() {
+        let expected_leaf = keccak::hashv(&[
+            &[LEAF_PREFIX, LEAF_VERSION_V1],
+            wallet(1).as_ref(),
+            &7u64.to_le_bytes(),
+            &100u64.to_le_bytes(),
+            &10u64.to_le_bytes(),
+        ])
+        .0;
+        assert_eq!(compute_leaf(LEAF_VERSION_V1, &wallet(1), 7, 100, 10), expected_leaf);
+
+        let (a, b) = ([1u8; 32], [2u8; 32]);
+        let expected_node = keccak::hashv(&[&[NODE_PREFIX], &a, &b]).0;
+        assert_eq!(hash_node(LEAF_VERSION_V1, &a, &b), expected_node);
+        assert_eq!(hash_node(LEAF_VERSION_V1, &b, &a), expected_node);
+    }
+
+    #[test]
+    fn proofs_verify_for_each_version() {
+        for leaf_version in [LEAF_VERSION_LEGACY, LEAF_VERSION_V1] {
+            let (leaves, root) = four_leaf_tree(leaf_version);
+            let sibling_pair = hash_node(leaf_version, &leaves[2], &leaves[3]);
+            assert!(verify_proof(leaf_version, &[leaves[1], sibling_pair], root, leaves[0]));
+
+            let tampered = compute_leaf(leaf_version, &wallet(1), 7, 101, 10);
+            assert!(!verify_proof(leaf_version, &[leaves[1], sibling_pair], root, tampered));
+        }
+    }
+
+    #[test]
+    fn versions_do_not_cross_verify() {
+        let (legacy_leaves, legacy_root) = four_leaf_tree(LEAF_VERSION_LEGACY);
+        let sibling_pair = hash_node(LEAF_VERSION_LEGACY, &legacy_leaves[2], &legacy_leaves[3]);
+        assert!(!verify_proof(
+            LEAF_VERSION_V1,
+            &[legacy_leaves[1], sibling_pair],
+            legacy_root,
+            legacy_leaves[0]
+        ));
+    }
+
+    #[test]
+    fn v1_leaf_cannot_reproduce_internal_node() {
+        // Legacy hashing of a child pair is indistinguishable from a 64-byte leaf preimage
+        let (leaves, _) = four_leaf_tree(LEAF_VERSION_LEGACY);
+        let (left, right) = if leaves[0] <= leaves[1] {
+            (leaves[0], leaves[1])
+        } else {
+            (leaves[1], leaves[0])
+        };
+        assert_eq!(
+            keccak::hashv(&[&left, &right]).0,
+            hash_node(LEAF_VERSION_LEGACY, &left, &right)
+        );
+
+        // With domain prefixes the same bytes hashed as a leaf never match the node
+        let as_leaf = keccak::hashv(&[&[LEAF_PREFIX, LEAF_VERSION_V1], &left, &right]).0;
+        assert_ne!(as_leaf, hash_node(LEAF_VERSION_V1, &left, &right));
+    }
+
+    fn sums(sol: u64, token: u64) -> SubtreeSums {
+        SubtreeSums { sol, token }
+    }
+
+    /// Three-leaf sum tree: ((leaf0, leaf1), leaf2) with its root and root sums
+    fn sum_tree() -> ([[u8; 32]; 3], [SubtreeSums; 3], [u8; 32], SubtreeSums) {
+        let leaves = [
+            compute_leaf(LEAF_VERSION_SUM_TREE, &wallet(1), 9, 100, 1),
+            compute_leaf(LEAF_VERSION_SUM_TREE, &wallet(2), 9, 200, 2),
+            compute_leaf(LEAF_VERSION_SUM_TREE, &wallet(3), 9, 300, 3),
+        ];
+        let leaf_sums = [sums(100, 1), sums(200, 2), sums(300, 3)];
+        let pair = hash_sum_node(&leaves[0], &leaf_sums[0], &leaves[1], &leaf_sums[1]);
+        let root = hash_sum_node(&pair, &sums(300, 3), &leaves[2], &leaf_sums[2]);
+        (leaves, leaf_sums, root, sums(600, 6))
+    }
 
+    #[test]
+    fn sum_node_layout() {
+        let (a, b) = ([1u8; 32], [2u8; 32]);
+        let expected = keccak::hashv(&[
+            &[NODE_PREFIX],
+            &a,
+            &5u64.to_le_bytes(),
+            &6u64.to_le_bytes(),
+            &b,
+            &7u64.to_le_bytes(),
+            &8u64.to_le_bytes(),
+        ])
+        .0;
+        assert_eq!(hash_sum_node(&a, &sums(5, 6), &b, &sums(7, 8)), expected);
+        assert_eq!(hash_sum_node(&b, &sums(7, 8), &a, &sums(5, 6)), expected);
+        assert_ne!(expected, hash_node(LEAF_VERSION_V1, &a, &b));
+    }
+
+    #[test]
+    fn sum_proof_verifies_against_totals() {
+        let (leaves, leaf_sums, root, totals) = sum_tree();
+        let pair = hash_sum_node(&leaves[0], &leaf_sums[0], &leaves[1], &leaf_sums[1]);
+
+        assert!(verify_sum_proof(
+            &[leaves[1], leaves[2]],
+            &[leaf_sums[1], leaf_sums[2]],
+            root,
+            totals,
+            leaves[0],
+            leaf_sums[0]
+        ));
+        assert!(verify_sum_proof(&[pair], &[sums(300, 3)], root, totals, leaves[2], leaf_sums[2]));
+    }
+
+    #[test]
+    fn sum_proof_rejects_total_mismatch() {
+        let (leaves, leaf_sums, root, _) = sum_tree();
+        let proof = [leaves[1], leaves[2]];
+        let proof_sums = [leaf_sums[1], leaf_sums[2]];
+
+        // Published totals that differ from the committed sums
+        assert!(!verify_sum_proof(&proof, &proof_sums, root, sums(700, 6), leaves[0], leaf_sums[0]));
+        assert!(!verify_sum_proof(&proof, &proof_sums, root, sums(600, 5), leaves[0], leaf_sums[0]));
+
+        // A sibling sum shifted to inflate the claimant's leaf changes the root
+        assert!(!verify_sum_proof(
+            &proof,
+            &[sums(100, 2), leaf_sums[2]],
+            root,
+            sums(600, 6),
+            leaves[0],
+            sums(200, 1)
+        ));
+
+        // Proof and sums of different lengths
+        assert!(!verify_sum_proof(&proof, &proof_sums[..1], root, sums(600, 6), leaves[0], leaf_sums[0]));
+    }
+
+    fn vesting() -> VestingSchedule {
+        VestingSchedule {
+            start_time: 1_000,
+            cliff_seconds: 100,
+            duration_seconds: 400,
+        }
+    }
+
+    #[test]
+    fn vesting_nothing_before_cliff() {
+        let schedule = vesting();
+        assert_eq!(schedule.vested_amount(4_000, 500), 0);
+        assert_eq!(schedule.vested_amount(4_000, 1_000), 0);
+        assert_eq!(schedule.vested_amount(4_000, 1_099), 0);
+        // The cliff releases everything accrued since the start
+        assert_eq!(schedule.vested_amount(4_000, 1_100), 1_000);
+    }
+
+    #[test]
+    fn vesting_linear_midpoint() {
+        let schedule = vesting();
+        assert_eq!(schedule.vested_amount(4_000, 1_200), 2_000);
+        assert_eq!(schedule.vested_amount(4_001, 1_200), 2_000);
+        assert!(!schedule.is_complete(1_200));
+    }
+
+    #[test]
+    fn vesting_completes_at_duration() {
+        let schedule = vesting();
+        assert_eq!(schedule.vested_amount(4_000, 1_399), 3_990);
+        assert_eq!(schedule.vested_amount(4_000, 1_400), 4_000);
+        assert_eq!(schedule.vested_amount(4_000, i64::MAX), 4_000);
+        assert!(!schedule.is_complete(1_399));
+        assert!(schedule.is_complete(1_400));
+
+        let immediate = VestingSchedule::default();
+        assert_eq!(immediate.vested_amount(4_000, 0), 4_000);
+        assert!(immediate.is_complete(0));
+    }
+
+    #[test]
+    fn vesting_large_amounts_do_not_overflow() {
+        let schedule = VestingSchedule {
+            start_time: 0,
+            cliff_seconds: 0,
+            duration_seconds: i64::MAX,
+        };
+        let half = schedule.vested_amount(u64::MAX, i64::MAX / 2);
+        assert!(half > u64::MAX / 2 - 2 && half <= u64::MAX / 2);
+        assert_eq!(vesting().vested_amount(u64::MAX, 1_200), u64::MAX / 2);
+    }
+
+    #[test]
+    fn vesting_schedule_validation() {
+        assert!(vesting().validate());
+        assert!(VestingSchedule::default().validate());
+        assert!(!VestingSchedule { cliff_seconds: 500, ..vesting() }.validate());
+        assert!(!VestingSchedule { cliff_seconds: -1, ..vesting() }.validate());
+        assert!(!VestingSchedule { duration_seconds: -1, cliff_seconds: 0, ..vesting() }.validate());
+    }
+}
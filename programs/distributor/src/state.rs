@@ -1,13 +1,30 @@
 use anchor_lang::prelude::*;
+use crate::error::DistributorError;
+use crate::{EpochMetadata, SponsorConfig, VestingSchedule};
 
-/// Global distributor state
+/// Longest challenge period the admin may configure (7 days)
+pub const MAX_CHALLENGE_PERIOD: i64 = 7 * 86400;
+/// Seconds after finalization before the admin may close receipts holders left open (30 days)
+pub const RECEIPT_CLOSE_GRACE_PERIOD: i64 = 30 * 86400;
+/// Maximum number of keepers in the publishing set
+pub const MAX_KEEPERS: usize = 5;
+/// Maximum length of the allocation dataset URI
+pub const MAX_METADATA_URI_LEN: usize = 200;
+
+/// Distributor instance state, one per flywheel token mint
 #[account]
 #[derive(Default)]
 pub struct DistributorState {
     /// Admin authority (should be multisig)
     pub admin: Pubkey,
-    /// Keeper authority for publishing epochs
-    pub keeper: Pubkey,
+    /// Flywheel token mint; namespaces this instance's PDAs
+    pub mint: Pubkey,
+    /// Keeper set allowed to attest epoch roots
+    pub keepers: [Pubkey; MAX_KEEPERS],
+    /// Number of active keepers
+    pub num_keepers: u8,
+    /// Matching keeper attestations required before a root goes live
+    pub publish_threshold: u8,
     /// SOL rewards vault bump
     pub sol_vault_bump: u8,
     /// Token rewards vault bump (if applicable)
@@ -22,6 +39,14 @@ pub struct DistributorState {
     pub total_token_distributed: u64,
     /// Paused flag
     pub paused: bool,
+    /// Guardian authority that may veto epochs during the challenge period
+    pub guardian: Pubkey,
+    /// Seconds a published root must wait before it becomes claimable
+    pub challenge_period: i64,
+    /// Last slot covered by a published epoch; the next epoch starts after it
+    pub last_covered_slot: u64,
+    /// Key that signs claim vouchers (default = vouchers disabled)
+    pub voucher_signer: Pubkey,
     /// Reserved for future use
     pub _reserved: [u8; 64],
 }
@@ -29,7 +54,10 @@ pub struct DistributorState {
 impl DistributorState {
     pub const LEN: usize = 8 + // discriminator
         32 + // admin
-        32 + // keeper
+        32 + // mint
+        (32 * MAX_KEEPERS) + // keepers
+        1 +  // num_keepers
+        1 +  // publish_threshold
         1 +  // sol_vault_bump
         1 +  // token_vault_bump
         1 +  // bump
@@ -37,7 +65,27 @@ impl DistributorState {
         8 +  // total_sol_distributed
         8 +  // total_token_distributed
         1 +  // paused
+        32 + // guardian
+        8 +  // challenge_period
+        8 +  // last_covered_slot
+        32 + // voucher_signer
         64;  // reserved
+
+    pub fn is_keeper(&self, key: &Pubkey) -> bool {
+        self.keepers[..self.num_keepers as usize].contains(key)
+    }
+}
+
+/// Lifecycle of a published epoch root
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EpochStatus {
+    /// Root published; claimable once the challenge period has elapsed
+    #[default]
+    Published,
+    /// Root vetoed during the challenge period; awaiting a corrected root
+    Vetoed,
+    /// Waiting for matching keeper attestations to reach the publish threshold
+    Attesting,
 }
 
 /// Per-epoch state with merkle root and totals
@@ -66,8 +114,38 @@ pub struct EpochState {
     pub num_claimants: u32,
     /// Bump for PDA
     pub bump: u8,
+    /// Keeper that published the epoch (receives rent when closed)
+    pub publisher: Pubkey,
+    /// Epoch closed to new claims; receipts may be closed once set
+    pub finalized: bool,
+    /// Number of claim receipts closed after finalization
+    pub num_receipts_closed: u32,
+    /// Leaf/node hashing scheme used by the merkle tree (0 = legacy)
+    pub leaf_version: u8,
+    /// Publication status
+    pub status: EpochStatus,
+    /// Timestamp after which the root becomes claimable
+    pub claimable_at: i64,
+    /// Keepers that attested the current root
+    pub attesters: [Pubkey; MAX_KEEPERS],
+    /// Number of matching attestations recorded
+    pub num_attestations: u8,
+    /// Attestations required, fixed when the epoch is created
+    pub required_attestations: u8,
+    /// Commitment to the allocation dataset behind the root
+    pub metadata: EpochMetadata,
+    /// Declared uncovered slots between the previous epoch and `start_slot`
+    pub gap_slots: u64,
+    /// Vesting schedule applied to each allocation
+    pub vesting: VestingSchedule,
+    /// Claims whose rent was covered by the sponsor pool
+    pub num_sponsored_claims: u32,
+    /// Reward mint distributions attached to this epoch and not yet closed
+    pub num_reward_mints: u16,
+    /// Timestamp the epoch was finalized
+    pub finalized_at: i64,
     /// Reserved for future use
-    pub _reserved: [u8; 32],
+    pub _reserved: [u8; 31],
 }
 
 impl EpochState {
@@ -83,10 +161,83 @@ impl EpochState {
         8 +  // publish_time
         4 +  // num_claimants
         1 +  // bump
-        32;  // reserved
+        32 + // publisher
+        1 +  // finalized
+        4 +  // num_receipts_closed
+        1 +  // leaf_version
+        1 +  // status
+        8 +  // claimable_at
+        (32 * MAX_KEEPERS) + // attesters
+        1 +  // num_attestations
+        1 +  // required_attestations
+        32 + // metadata.content_hash
+        4 + MAX_METADATA_URI_LEN + // metadata.uri
+        4 +  // metadata.leaf_count
+        2 +  // metadata.methodology_version
+        8 +  // gap_slots
+        24 + // vesting
+        4 +  // num_sponsored_claims
+        2 +  // num_reward_mints
+        8 +  // finalized_at
+        31;  // reserved
+
+    /// Root survived its challenge period and may be claimed against
+    pub fn is_claimable(&self, now: i64) -> bool {
+        self.status == EpochStatus::Published && now >= self.claimable_at
+    }
+
+    /// Root may still be vetoed or replaced
+    pub fn is_challengeable(&self, now: i64) -> bool {
+        self.status != EpochStatus::Published || now < self.claimable_at
+    }
+
+    pub fn has_attested(&self, key: &Pubkey) -> bool {
+        self.attesters[..self.num_attestations as usize].contains(key)
+    }
+
+    /// Record a keeper attestation for the current root. Once the threshold is
+    /// reached the root is published and its challenge period starts.
+    pub fn record_attestation(&mut self, keeper: Pubkey, now: i64, challenge_period: i64) -> Result<()> {
+        require!(
+            !self.has_attested(&keeper),
+            DistributorError::AlreadyAttested
+        );
+        require!(
+            (self.num_attestations as usize) < MAX_KEEPERS,
+            DistributorError::MaxKeepersReached
+        );
+
+        self.attesters[self.num_attestations as usize] = keeper;
+        self.num_attestations += 1;
+
+        if self.num_attestations >= self.required_attestations {
+            self.status = EpochStatus::Published;
+            self.claimable_at = now
+                .checked_add(challenge_period)
+                .ok_or(DistributorError::MathOverflow)?;
+        } else {
+            self.status = EpochStatus::Attesting;
+        }
+
+        Ok(())
+    }
+
+    /// Clear attestations so a replacement root starts collecting afresh
+    pub fn reset_attestations(&mut self, required_attestations: u8) {
+        self.attesters = [Pubkey::default(); MAX_KEEPERS];
+        self.num_attestations = 0;
+        self.required_attestations = required_attestations;
+    }
+
+    /// Finalized, every claim receipt closed and every reward mint distribution closed
+    pub fn is_settled(&self) -> bool {
+        self.finalized
+            && self.num_receipts_closed == self.num_claimants
+            && self.num_reward_mints == 0
+    }
 }
 
-/// Claim receipt - tracks that a wallet has claimed for an epoch
+/// Claim receipt - tracks a wallet's allocation and releases for an epoch
 #[account]
 #[derive(Default)]
 pub struct ClaimReceipt {
@@ -94,14 +245,20 @@ pub struct ClaimReceipt {
     pub wallet: Pubkey,
     /// Epoch ID
     pub epoch_id: u64,
-    /// SOL amount claimed
+    /// SOL allocation from the merkle leaf
     pub amount_sol: u64,
-    /// Token amount claimed
+    /// Token allocation from the merkle leaf
     pub amount_token: u64,
-    /// Timestamp of claim
+    /// Timestamp of the most recent claim
     pub claimed_at: i64,
     /// Bump for PDA
     pub bump: u8,
+    /// SOL released so far
+    pub released_sol: u64,
+    /// Tokens released so far (accounting only; the merkle claim pays SOL)
+    pub released_token: u64,
+    /// Account that paid the receipt rent (claimant or relayer); refunded on close
+    pub rent_payer: Pubkey,
 }
 
 impl ClaimReceipt {
@@ -111,7 +268,217 @@ impl ClaimReceipt {
         8 +  // amount_sol
         8 +  // amount_token
         8 +  // claimed_at
+        1 +  // bump
+        8 +  // released_sol
+        8 +  // released_token
+        32;  // rent_payer
+
+    /// Entire allocation has been released
+    pub fn is_fully_released(&self) -> bool {
+        self.released_sol == self.amount_sol && self.released_token == self.amount_token
+    }
+}
+
+/// Sponsor pool - program-owned SOL balance that reimburses relayers for the
+/// receipt rent of small claims
+#[account]
+#[derive(Default)]
+pub struct SponsorPool {
+    /// Sponsorship limits
+    pub config: SponsorConfig,
+    /// Total sponsored claims
+    pub total_sponsored_claims: u64,
+    /// Total lamports paid out to relayers
+    pub total_lamports_reimbursed: u64,
+    /// Bump for PDA
+    pub bump: u8,
+}
+
+impl SponsorPool {
+    pub const LEN: usize = 8 + // discriminator
+        8 +  // config.max_claim_lamports
+        8 +  // config.max_claim_token
+        8 +  // config.fee_reimbursement_lamports
+        4 +  // config.max_claims_per_wallet
+        4 +  // config.max_claims_per_epoch
+        8 +  // total_sponsored_claims
+        8 +  // total_lamports_reimbursed
+        1;   // bump
+}
+
+/// Per-wallet sponsorship usage, enforcing the per-wallet cap
+#[account]
+#[derive(Default)]
+pub struct SponsorUsage {
+    /// Wallet being sponsored
+    pub wallet: Pubkey,
+    /// Sponsored claims so far
+    pub sponsored_claims: u32,
+    /// Bump for PDA
+    pub bump: u8,
+    /// Sponsored claim receipts not yet closed; the usage closes once this is zero
+    pub open_receipts: u32,
+}
+
+impl SponsorUsage {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // wallet
+        4 +  // sponsored_claims
+        1 +  // bump
+        4;   // open_receipts
+}
+
+/// Registered reward mint - an additional token distributed through epoch trees
+#[account]
+#[derive(Default)]
+pub struct RewardMint {
+    /// Token mint
+    pub mint: Pubkey,
+    /// Token vault holding this mint's rewards (owned by this PDA)
+    pub vault: Pubkey,
+    /// Whether new epochs may distribute this mint
+    pub enabled: bool,
+    /// Total distributed across all epochs
+    pub total_distributed: u64,
+    /// Bump for PDA
+    pub bump: u8,
+    /// Vault PDA bump
+    pub vault_bump: u8,
+}
+
+impl RewardMint {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        32 + // vault
+        1 +  // enabled
+        8 +  // total_distributed
+        1 +  // bump
+        1;   // vault_bump
+}
+
+/// Per-epoch totals for one registered reward mint
+#[account]
+#[derive(Default)]
+pub struct EpochMintRewards {
+    /// Epoch identifier
+    pub epoch_id: u64,
+    /// Token mint
+    pub mint: Pubkey,
+    /// Total rewards of this mint for the epoch
+    pub total_rewards: u64,
+    /// Total claimed so far
+    pub claimed: u64,
+    /// Number of unique claimants
+    pub num_claimants: u32,
+    /// Number of claim receipts closed after finalization
+    pub num_receipts_closed: u32,
+    /// Keeper that declared the totals (receives rent when closed)
+    pub publisher: Pubkey,
+    /// Bump for PDA
+    pub bump: u8,
+}
+
+impl EpochMintRewards {
+    pub const LEN: usize = 8 + // discriminator
+        8 +  // epoch_id
+        32 + // mint
+        8 +  // total_rewards
+        8 +  // claimed
+        4 +  // num_claimants
+        4 +  // num_receipts_closed
+        32 + // publisher
+        1;   // bump
+}
+
+/// Claim receipt for a reward mint allocation
+#[account]
+#[derive(Default)]
+pub struct MintClaimReceipt {
+    /// Wallet that claimed
+    pub wallet: Pubkey,
+    /// Epoch ID
+    pub epoch_id: u64,
+    /// Token mint
+    pub mint: Pubkey,
+    /// Allocation from the merkle leaf
+    pub amount: u64,
+    /// Released so far
+    pub released: u64,
+    /// Timestamp of the most recent claim
+    pub claimed_at: i64,
+    /// Account that paid the receipt rent; refunded on close
+    pub rent_payer: Pubkey,
+    /// Bump for PDA
+    pub bump: u8,
+}
+
+impl MintClaimReceipt {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // wallet
+        8 +  // epoch_id
+        32 + // mint
+        8 +  // amount
+        8 +  // released
+        8 +  // claimed_at
+        32 + // rent_payer
         1;   // bump
 }
 
+/// Payout cap for a voucher epoch
+#[account]
+#[derive(Default)]
+pub struct VoucherBudget {
+    /// Voucher epoch ID
+    pub epoch_id: u64,
+    /// Maximum SOL payable by vouchers of this epoch
+    pub budget_sol: u64,
+    /// Maximum tokens payable by vouchers of this epoch
+    pub budget_token: u64,
+    /// SOL paid so far
+    pub paid_sol: u64,
+    /// Tokens paid so far
+    pub paid_token: u64,
+    /// Vouchers redeemed
+    pub num_redeemed: u32,
+    /// Bump for PDA
+    pub bump: u8,
+    /// Last moment any voucher of this epoch may be redeemed
+    pub expires_at: i64,
+    /// Redeemed nonces closed after expiry
+    pub num_nonces_closed: u32,
+}
+
+impl VoucherBudget {
+    pub const LEN: usize = 8 + // discriminator
+        8 +  // epoch_id
+        8 +  // budget_sol
+        8 +  // budget_token
+        8 +  // paid_sol
+        8 +  // paid_token
+        4 +  // num_redeemed
+        1 +  // bump
+        8 +  // expires_at
+        4;   // num_nonces_closed
+}
+
+/// Marks a voucher nonce as redeemed
+#[account]
+#[derive(Default)]
+pub struct VoucherNonce {
+    /// Wallet that redeemed the voucher
+    pub wallet: Pubkey,
+    /// Voucher epoch the payout counted against
+    pub epoch_id: u64,
+    /// Redemption timestamp
+    pub redeemed_at: i64,
+    /// Bump for PDA
+    pub bump: u8,
+}
 
+impl VoucherNonce {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // wallet
+        8 +  // epoch_id
+        8 +  // redeemed_at
+        1;   // bump
+}